@@ -0,0 +1,102 @@
+use sails_rs::{
+    prelude::*
+};
+use gstd::MessageId;
+use super::{
+    message::MessageBuilder,
+    error::BuilderError
+};
+
+/// Accumulates several configured `MessageBuilder`s to send them together.
+///
+/// Useful when a contract needs to dispatch a heterogeneous set of messages and wants to handle
+/// the partial failures in a single place.
+pub struct MessageBatch {
+    messages: Vec<MessageBuilder>,
+}
+
+impl MessageBatch {
+    pub fn new() -> Self {
+        Self {
+            messages: vec![]
+        }
+    }
+
+    /// ## Add a configured message to the batch
+    /// Messages will be sent in the same order that they were added
+    pub fn push(mut self, message: MessageBuilder) -> Self {
+        self.messages.push(message);
+
+        self
+    }
+
+    /// ## Number of messages in the batch
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// ## Check if the batch has no messages
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// ## Send all the messages of the batch
+    /// Messages are sent one by one with `send_checked`, in the same order that they were added, and the
+    /// result of each send is stored in the returned vec at the same index of its message. A message with
+    /// missing or invalid data (like the destination or a half-set route) gets its error in its slot
+    /// without sending it, and a failed send does not stop the rest of the messages from being sent.
+    pub fn send_all(self) -> Vec<Result<MessageId, BuilderError>> {
+        self.send_each(MessageBuilder::send_checked)
+    }
+
+    fn send_each<F>(self, mut send: F) -> Vec<Result<MessageId, BuilderError>>
+    where
+        F: FnMut(MessageBuilder) -> Result<MessageId, BuilderError>,
+    {
+        self.messages
+            .into_iter()
+            .map(|message| {
+                message.try_check_data()?;
+
+                send(message)
+            })
+            .collect()
+    }
+}
+
+impl Default for MessageBatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn send_all_returns_per_item_results() {
+        let destination = ActorId::from(1u64);
+        let batch = MessageBatch::new()
+            .push(MessageBuilder::new().send_to(destination))
+            .push(MessageBuilder::new())
+            .push(MessageBuilder::new().send_to(destination).service_name("Service"))
+            .push(MessageBuilder::new().send_to(ActorId::zero()))
+            .push(MessageBuilder::new().send_to(destination).service_name("Service").method_name("Method"));
+
+        let mut sent = 0u64;
+        let results = batch.send_each(|_| {
+            sent += 1;
+
+            Ok(MessageId::from(sent))
+        });
+
+        assert_eq!(sent, 2);
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[0].as_ref().unwrap(), &MessageId::from(1u64));
+        assert!(matches!(results[1], Err(BuilderError::MissingDestination)));
+        assert!(matches!(results[2], Err(BuilderError::IncompleteRoute)));
+        assert!(matches!(results[3], Err(BuilderError::ZeroAddress)));
+        assert_eq!(results[4].as_ref().unwrap(), &MessageId::from(2u64));
+    }
+}
@@ -0,0 +1,42 @@
+use gstd::errors::Error;
+
+/// Errors returned by the checked methods of the `MessageBuilder`
+#[derive(Debug)]
+pub enum BuilderError {
+    /// The destination of the message was not set
+    MissingDestination,
+    /// Only one of the service and method names was set
+    IncompleteRoute,
+    /// The destination of the message is the zero address (the value would be burned)
+    ZeroAddress,
+    /// The value to send exceeds the value available in the program
+    InsufficientValue {
+        required: u128,
+        available: u128,
+    },
+    /// The treasury fee set with `with_treasury_fee` exceeds the value of the message
+    FeeExceedsValue {
+        fee: u128,
+        value: u128,
+    },
+    /// The arguments of the message exceed the max size set with `with_max_payload`
+    PayloadTooLarge {
+        len: usize,
+        max: usize,
+    },
+    /// The arguments of the message don't have the size set with `expect_payload_len`
+    PayloadLenMismatch {
+        len: usize,
+        expected: usize,
+    },
+    /// The destination was not marked as a contract with `assume_contract_destination`
+    UnverifiedContractDestination,
+    /// Error returned by gstd while sending the message
+    Send(Error),
+}
+
+impl From<Error> for BuilderError {
+    fn from(error: Error) -> Self {
+        BuilderError::Send(error)
+    }
+}
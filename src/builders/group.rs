@@ -0,0 +1,71 @@
+use sails_rs::{
+    prelude::*
+};
+use gstd::{
+    msg::MessageFuture,
+    errors::Error
+};
+use super::message::MessageBuilder;
+use crate::funcs;
+
+/// Sends several messages for reply and awaits all the replies.
+///
+/// The messages are sent when they are added to the group, so all the destinations handle them
+/// concurrently, and `await_all` only waits for the replies.
+pub struct MessageGroup {
+    futures: Vec<Result<MessageFuture, Error>>,
+}
+
+impl MessageGroup {
+    pub fn new() -> Self {
+        Self {
+            futures: vec![]
+        }
+    }
+
+    /// ## Send a message for reply and add it to the group
+    /// The message is sent with `send_recv_future`, if the send fails, the error will be returned by
+    /// `await_all` in the position of the message
+    pub fn push(mut self, message: MessageBuilder) -> Self {
+        self.futures.push(message.send_recv_future());
+
+        self
+    }
+
+    /// ## Number of messages in the group
+    pub fn len(&self) -> usize {
+        self.futures.len()
+    }
+
+    /// ## Check if the group has no messages
+    pub fn is_empty(&self) -> bool {
+        self.futures.is_empty()
+    }
+
+    /// ## Await the replies of all the messages
+    /// All the replies are decoded as `R` (with the sails format, like `send_recv`), so all the
+    /// messages of the group must reply with the same type. The results are returned in the same
+    /// order that the messages were added.
+    pub async fn await_all<R: Decode>(self) -> Vec<Result<R, Error>> {
+        let mut results = Vec::with_capacity(self.futures.len());
+
+        for future in self.futures {
+            let result = match future {
+                Ok(future) => future
+                    .await
+                    .and_then(|reply| funcs::decode_sails_reply::<R>(&reply)),
+                Err(error) => Err(error),
+            };
+
+            results.push(result);
+        }
+
+        results
+    }
+}
+
+impl Default for MessageGroup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,31 @@
+use sails_rs::{
+    prelude::*
+};
+use gstd::MessageId;
+
+/// Record of a delayed message sent by the `MessageBuilder`, to correlate it later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, TypeInfo)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct DelayedHandle {
+    /// Id of the delayed message
+    pub message_id: MessageId,
+    /// Destination of the message
+    pub to: ActorId,
+    /// Block at which the message is scheduled to be executed
+    pub fire_block: u32,
+}
+
+/// Record of a message sent for reply by the `MessageBuilder` without waiting for the reply, to match
+/// the reply in a later execution (in `handle_reply`, with `msg::reply_to()`)
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub struct PendingReply {
+    /// Id of the message sent, the reply will have it as `reply_to`
+    pub message_id: MessageId,
+    /// Tag set by the caller to identify the type expected in the reply
+    pub reply_tag: String,
+    /// Block at which the message was sent
+    pub sent_block: u32,
+}
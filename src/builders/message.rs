@@ -2,20 +2,42 @@ use sails_rs::{
     prelude::*
 };
 use gstd::{
-    msg,
+    msg::{self, MessageFuture},
+    exec,
     MessageId,
     errors::Error,
     ReservationId
 };
-use crate::funcs;
+use crate::{
+    funcs,
+    consts::{ONE_TOKEN, BLOCK_TIME_MS}
+};
+use super::{
+    error::BuilderError,
+    snapshot::BuilderSnapshot,
+    handle::{DelayedHandle, PendingReply}
+};
+
+/// Argument added with `add_arg_with`, encoded when the request is built
+type LazyArg = Box<dyn FnOnce() -> Vec<u8>>;
 
 pub struct MessageBuilder {
     to: Option<ActorId>,
     service_name: Option<&'static str>,
     method_name: Option<&'static str>,
     payload: Option<Vec<u8>>,
+    lazy_args: Vec<(usize, LazyArg)>,
+    arg_starts: Vec<usize>,
+    compact: bool,
     delayed_duration: u32,
     delayed_with_gas: Option<u64>,
+    reply_deadline: Option<u32>,
+    gas_for_payload: Option<(u64, u64)>,
+    max_payload: Option<usize>,
+    expected_payload_len: Option<usize>,
+    reply_deposit: u64,
+    treasury_fee: Option<(ActorId, u128)>,
+    contract_destination: bool,
     value: u128,
 }
 
@@ -26,8 +48,18 @@ impl MessageBuilder {
             service_name: None,
             method_name: None,
             payload: None,
+            lazy_args: vec![],
+            arg_starts: vec![],
+            compact: false,
             delayed_duration: 1,
             delayed_with_gas: None,
+            reply_deadline: None,
+            gas_for_payload: None,
+            max_payload: None,
+            expected_payload_len: None,
+            reply_deposit: 0,
+            treasury_fee: None,
+            contract_destination: false,
             value: 0
         }
     }
@@ -54,6 +86,85 @@ impl MessageBuilder {
         self
     }
 
+    /// ## Set the max blocks to wait for the reply
+    /// Only works with `send_recv` methods, if the reply does not arrive in the given blocks, the
+    /// send will return a timeout error. The deadline is at least 1 block (0 is rounded up to 1, since
+    /// gstd can't wait for 0 blocks). By default it waits up to gstd `Config::wait_up_to()` blocks (100)
+    pub fn reply_deadline_blocks(mut self, blocks: u32) -> Self {
+        self.reply_deadline = Some(blocks.max(1));
+
+        self
+    }
+
+    /// ## Set the max time in milliseconds to wait for the reply
+    /// Same as `reply_deadline_blocks`, but the time is converted to blocks using `BLOCK_TIME_MS`,
+    /// rounding up (for example, 4_000 ms will wait up to 2 blocks, and 0 ms up to 1 block)
+    pub fn reply_timeout_ms(self, ms: u64) -> Self {
+        let blocks = ms
+            .div_ceil(BLOCK_TIME_MS)
+            .min(u32::MAX as u64) as u32;
+
+        self.reply_deadline_blocks(blocks)
+    }
+
+    /// ## Set the gas of the message proportional to its size
+    /// When the message is sent, the gas limit will be `base + per_byte * len`, where `len` is the size in
+    /// bytes of the full request (service and method names with the arguments). By default the message is
+    /// sent without a gas limit.
+    ///
+    /// It is ignored by the methods that get the gas from elsewhere: the reservation methods, `send_delayed`
+    /// with `delayed_with_gas`, and `send_with_gas_or_reservation`
+    pub fn with_gas_for_payload(mut self, base: u64, per_byte: u64) -> Self {
+        self.gas_for_payload = Some((base, per_byte));
+
+        self
+    }
+
+    /// ## Set the max size in bytes of the arguments of the message
+    /// If the size of the arguments exceeds `max` when the message is sent, `send_checked` and `distribute`
+    /// will return `BuilderError::PayloadTooLarge`, and the other send methods will panic with it. The size
+    /// is checked after building the lazy arguments and dropping the empty ones (see `compact`).
+    /// `validate_only` checks `payload_len()`, that does not include the lazy arguments
+    pub fn with_max_payload(mut self, max: usize) -> Self {
+        self.max_payload = Some(max);
+
+        self
+    }
+
+    /// ## Deposit gas for the reply of the message
+    /// Only works with `send_recv` methods, the estimated gas of the reply (`estimate_reply_gas`) is
+    /// deposited when the message is sent, so the destination does not need to pay for the reply.
+    /// The deposited gas is taken from the gas of the current execution. By default there is no deposit
+    pub fn with_auto_reply_deposit(mut self) -> Self {
+        self.reply_deposit = funcs::estimate_reply_gas();
+
+        self
+    }
+
+    /// ## Set the exact size in bytes that the arguments of the message must have
+    /// Defensive check for protocols with fixed size payloads, if the size of the arguments is different from
+    /// `expected` when the message is sent (for example, an argument was omitted), `send_checked` and
+    /// `distribute` will return `BuilderError::PayloadLenMismatch`, and the other send methods will panic
+    /// with it. As in `with_max_payload`, the size includes the lazy arguments
+    pub fn expect_payload_len(mut self, expected: usize) -> Self {
+        self.expected_payload_len = Some(expected);
+
+        self
+    }
+
+    /// ## Drop the trailing empty arguments of the message
+    /// When the message is sent, the trailing arguments whose encoding is a single zero byte (`None`,
+    /// empty `Vec` or `String`, `false`, `0u8`) are removed from the payload, reducing the message size.
+    /// The arguments added as the first argument (like `with_nonce` or `with_schema_version`) are never removed.
+    ///
+    /// Note: the receiver must support the missing arguments (for example, decoding them as defaults
+    /// when the payload ends), a sails method will fail to decode a payload with missing arguments
+    pub fn compact(mut self) -> Self {
+        self.compact = true;
+
+        self
+    }
+
     /// ## Set the service name to call
     /// If you set the service name, you have to set the method name too, because it will send the 
     /// message to a sails contract
@@ -92,11 +203,187 @@ impl MessageBuilder {
             .payload
             .get_or_insert(vec![]);
 
+        self.arg_starts.push(payload_vec.len());
         arg.encode_to(payload_vec);
 
         self
     }
 
+    /// ## Add a u64 argument
+    /// Same as `add_arg(value)`, SCALE encodes integers in little-endian, so the 8 bytes are sent from the
+    /// least significant to the most significant byte
+    pub fn add_u64(self, value: u64) -> Self {
+        self.add_arg(value)
+    }
+
+    /// ## Add a u64 argument in little-endian
+    /// Same as `add_u64`, the name makes explicit the byte order, that is the one expected by a receiver
+    /// decoding a `u64` argument
+    pub fn add_u64_le(self, value: u64) -> Self {
+        self.add_arg(value)
+    }
+
+    /// ## Add a u64 as raw big-endian bytes
+    /// Appends the 8 bytes from the most significant to the least significant byte, for receivers that
+    /// expect big-endian integers (like some cross-language integrations). A receiver decoding a `u64`
+    /// will get a different value, it has to decode a `[u8; 8]` and use `u64::from_be_bytes`
+    pub fn add_u64_be_bytes(self, value: u64) -> Self {
+        self.add_arg(value.to_be_bytes())
+    }
+
+    /// ## Add a binary blob to the message
+    /// The bytes are encoded as a `Vec<u8>` (a compact length prefix followed by the bytes), so the
+    /// receiving method has to decode it as a `Vec<u8>` argument. It is the same as `add_arg(bytes.to_vec())`,
+    /// but without copying the bytes
+    pub fn add_blob(self, bytes: &[u8]) -> Self {
+        self.add_arg(bytes)
+    }
+
+    /// ## Add pre-encoded fields to the message
+    /// The bytes of each field are appended as they are (they must be already encoded), in the same order
+    /// of the slice, the names are only for the caller bookkeeping and are not sent. Useful to build payloads
+    /// from dynamic schemas, the order must match the arguments of the method called
+    pub fn add_fields(mut self, fields: &[(&str, Vec<u8>)]) -> Self {
+        let payload_vec = self
+            .payload
+            .get_or_insert(vec![]);
+
+        for (_, field) in fields {
+            self.arg_starts.push(payload_vec.len());
+            payload_vec.extend_from_slice(field);
+        }
+
+        self
+    }
+
+    /// ## Add an argument built lazily
+    /// Same as `add_arg`, but the argument is built by the closure when the message is sent, so if the
+    /// message is not sent, the closure is never called (useful if the argument is expensive to build).
+    ///
+    /// The argument keeps the position where the method is called, relative to the other arguments, and
+    /// the closures are called once, in the same order that they were added, when the request is built
+    /// (or when calling `expect_decodable`).
+    ///
+    /// Note: the closure is stored in the builder, so it must be `'static` and can't borrow the contract
+    /// state, move (or clone) the data that it needs into the closure
+    pub fn add_arg_with<F, T>(mut self, f: F) -> Self
+    where
+        F: FnOnce() -> T + 'static,
+        T: Encode,
+    {
+        let offset = self.payload.as_ref().map_or(0, Vec::len);
+
+        self.lazy_args.push((offset, Box::new(move || f().encode())));
+
+        self
+    }
+
+    /// ## Add a nonce to the message for replay protection
+    /// The nonce is added as the first argument of the message (after the service and method names),
+    /// no matter the position where the method is called, so the receiver can decode it first and
+    /// reject replayed messages using `check_and_consume_nonce`
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.prepend_arg(nonce.encode());
+
+        self
+    }
+
+    /// ## Add an idempotency key to the message
+    /// The key is added as the first argument of the message (the first 32 bytes after the service and
+    /// method names), no matter the position where the method is called, so the receiver can dedupe
+    /// messages that were delivered more than once. The receiver needs to keep track of the keys seen.
+    pub fn with_idempotency_key(mut self, key: [u8; 32]) -> Self {
+        self.prepend_arg(key.encode());
+
+        self
+    }
+
+    /// ## Add metadata to the message
+    /// The encoded metadata is added as the first argument of the message (after the service and method
+    /// names), no matter the position where the method is called, the receiver can use `decode_meta` to
+    /// decode it and get the rest of the arguments.
+    ///
+    /// Note: if you call several methods that add the first argument (like `with_nonce`), the last one
+    /// called will be the first
+    pub fn with_meta<M: Encode>(mut self, meta: M) -> Self {
+        self.prepend_arg(meta.encode());
+
+        self
+    }
+
+    /// ## Size in bytes of the arguments added to the message
+    /// It does not include the service and method names, nor the arguments added with `add_arg_with`
+    /// (they are built when the message is sent, or when calling `expect_decodable`)
+    pub fn payload_len(&self) -> usize {
+        self.payload.as_ref().map_or(0, Vec::len)
+    }
+
+    /// ## Add the original sender of a relayed message
+    /// The `original_sender` is added as the first argument of the message (after the service and method
+    /// names), no matter the position where the method is called, the final receiver can use
+    /// `decode_relay_sender` to get it.
+    ///
+    /// Note: the receiver can only trust the original sender if the relay contract (`msg::source()`
+    /// in the receiver) is trusted, since any contract can add any address
+    pub fn relay_from(mut self, original_sender: ActorId) -> Self {
+        self.prepend_arg(original_sender.encode());
+
+        self
+    }
+
+    /// ## Add a deadline block to the message
+    /// The deadline (u64) is added as the first argument of the message (after the service and method
+    /// names), no matter the position where the method is called, so the receiver can reject the
+    /// instruction with `is_expired` if it arrives after the deadline
+    pub fn with_deadline_block(mut self, block: u64) -> Self {
+        self.prepend_arg(block.encode());
+
+        self
+    }
+
+    /// ## Check if a gas budget is plausible for the message
+    /// Compares the `gas` with `estimate_message_gas(payload_len())`, it is a cheap heuristic to warn
+    /// before sending, not a guarantee that the gas is enough.
+    ///
+    /// Note: it only measures `payload_len()` (without the service and method names and the lazy
+    /// arguments), while `with_gas_for_payload` charges for the full request
+    pub fn fits_in_gas(&self, gas: u64) -> bool {
+        funcs::estimate_message_gas(self.payload_len()) <= gas
+    }
+
+    /// ## Add a schema version byte to the message
+    /// The version is added as the first byte of the arguments (after the service and method names), no
+    /// matter the position where the method is called, so the receiver can use `read_schema_version` to
+    /// branch on the message format
+    pub fn with_schema_version(mut self, version: u8) -> Self {
+        self.prepend_arg(version.encode());
+
+        self
+    }
+
+    /// ## Add a trace id to the message
+    /// The 16 bytes id is added as the first argument of the message (after the service and method names),
+    /// no matter the position where the method is called, so the receiver can use `extract_trace_id` to
+    /// get it and pass it to the next messages, tracing a request across several contracts
+    pub fn with_trace_id(mut self, id: [u8; 16]) -> Self {
+        self.prepend_arg(id.encode());
+
+        self
+    }
+
+    /// ## Add the current block height to the message
+    /// Appends `exec::block_height()` (u32) as an argument, in the same position where the method is called
+    pub fn add_current_block(self) -> Self {
+        self.add_arg(exec::block_height())
+    }
+
+    /// ## Add the current block timestamp to the message
+    /// Appends `exec::block_timestamp()` (u64, in milliseconds) as an argument, in the same position where
+    /// the method is called
+    pub fn add_current_timestamp(self) -> Self {
+        self.add_arg(exec::block_timestamp())
+    }
+
     /// ## Value to send in the message
     /// By default is zero, but you can set the number of Tokens to send in the message
     pub fn with_value(mut self, value: u128) -> Self {
@@ -105,14 +392,276 @@ impl MessageBuilder {
         self
     }
 
+    /// ## Value to send in the message, in whole tokens
+    /// Same as `with_value`, but the value is multiplied by `ONE_TOKEN`, for example
+    /// `with_tokens(5)` will send 5 Varas.
+    ///
+    /// It will panic if the value overflows
+    pub fn with_tokens(mut self, whole: u128) -> Self {
+        let Some(value) = whole.checked_mul(ONE_TOKEN) else {
+            funcs::panic("Value in tokens overflows");
+        };
+
+        self.value = value;
+
+        self
+    }
+
+    /// ## Set a minimum value to send in the message
+    /// The value will be the max between the value set (with `with_value` or `with_tokens`) and the
+    /// `floor`, useful to always send at least the existential deposit. Call it after setting the value,
+    /// since `with_value` will override it
+    pub fn with_value_at_least(mut self, floor: u128) -> Self {
+        self.value = self.value.max(floor);
+
+        self
+    }
+
+    /// ## Route a fee of the message value to a treasury
+    /// When the message is sent with `send_checked`, the remaining value (the value set minus the fee) is
+    /// attached to the message, and then `fee` is transferred to the `treasury` in a separate message
+    /// (without payload). The other send methods don't support the fee and will panic if it was set
+    pub fn with_treasury_fee(mut self, treasury: ActorId, fee: u128) -> Self {
+        self.treasury_fee = Some((treasury, fee));
+
+        self
+    }
+
+    /// ## Check that the arguments decode as the type expected by the destination
+    /// Decodes the arguments added so far (without the service and method names) as `T`, that must be
+    /// the tuple of arguments expected by the method of the destination, and returns `Error::Decode` if
+    /// the bytes are not a valid `T` or if bytes remain after decoding it. It catches argument mismatches
+    /// before sending the message.
+    ///
+    /// The lazy arguments (`add_arg_with`) added so far are built by this method, so they are included
+    pub fn expect_decodable<T: Decode>(mut self) -> Result<Self, Error> {
+        self.build_lazy_args();
+
+        let mut bytes = self.payload.as_deref().unwrap_or_default();
+
+        T::decode(&mut bytes).map_err(Error::Decode)?;
+
+        if !bytes.is_empty() {
+            return Err(Error::Decode("Arguments have trailing bytes".into()));
+        }
+
+        Ok(self)
+    }
+
+    /// ## Mark the destination as a contract
+    /// Programs can't check if an address has code, so the caller asserts that the destination is a
+    /// contract (for example, because its address was set in the program init), see
+    /// `ensure_contract_destination`
+    pub fn assume_contract_destination(mut self) -> Self {
+        self.contract_destination = true;
+
+        self
+    }
+
+    /// ## Check that the destination is a contract
+    /// Sending a sails request (service and method names) to a plain account will not execute anything,
+    /// and the value sent will be transferred to the account. The code of an address can't be queried
+    /// from a program, so this method checks that the destination was marked as a contract with
+    /// `assume_contract_destination`, returning `BuilderError::UnverifiedContractDestination` otherwise
+    /// (and `BuilderError::MissingDestination` if the destination was not set)
+    pub fn ensure_contract_destination(self) -> Result<Self, BuilderError> {
+        if self.to.is_none() {
+            return Err(BuilderError::MissingDestination);
+        }
+
+        if !self.contract_destination {
+            return Err(BuilderError::UnverifiedContractDestination);
+        }
+
+        Ok(self)
+    }
+
+    /// ## Validate the message without sending it
+    /// Runs the checks done before sending the message and returns the first error found:
+    /// - `BuilderError::MissingDestination` if the destination was not set.
+    /// - `BuilderError::ZeroAddress` if the destination is the zero address.
+    /// - `BuilderError::IncompleteRoute` if only one of the service and method names was set.
+    /// - `BuilderError::PayloadTooLarge` if the arguments exceed the max size set.
+    /// - `BuilderError::PayloadLenMismatch` if the arguments don't have the expected size.
+    /// - `BuilderError::FeeExceedsValue` if the treasury fee exceeds the value.
+    /// - `BuilderError::InsufficientValue` if the value exceeds `value_available()`.
+    ///
+    /// The size checks use `payload_len()`, the lazy arguments are only checked when the message is sent
+    pub fn validate_only(&self) -> Result<(), BuilderError> {
+        self.try_check_data()?;
+
+        self.check_payload(self.payload_len())?;
+
+        if let Some((_, fee)) = self.treasury_fee && fee > self.value {
+            return Err(BuilderError::FeeExceedsValue { fee, value: self.value });
+        }
+
+        let available = funcs::value_available();
+
+        if self.value > available {
+            return Err(BuilderError::InsufficientValue { required: self.value, available });
+        }
+
+        Ok(())
+    }
+
     /// ## Method to send the message
     pub fn send(mut self) -> Result<MessageId, Error> {
         self.check_data();
 
         let request = self.get_request();
 
-        msg::send_bytes(self.to.unwrap(), request, self.value)
-            .map_err(|error| Error::Core(error))
+        self.send_request(self.to.unwrap(), request, self.value)
+    }
+
+    /// ## Send the message and record it in an audit log
+    /// Before sending the message, a `BuilderSnapshot` with its data is pushed to the `sink`, so the
+    /// contract can have a deterministic audit trail of the messages sent. The sink is provided by the
+    /// caller, so it only lives during the current execution unless the contract stores it in its state.
+    pub fn send_with_audit(mut self, sink: &mut Vec<BuilderSnapshot>) -> Result<MessageId, Error> {
+        self.check_data();
+
+        let request = self.get_request();
+
+        sink.push(self.snapshot(&request, exec::block_height()));
+
+        self.send_request(self.to.unwrap(), request, self.value)
+    }
+
+    /// ## Send the message and track its id
+    /// Same as `send`, but if the message is sent, the `track` callback is called with its id (for
+    /// example, to store it in a pending messages map of the contract state)
+    pub fn send_and_track<F: FnMut(MessageId)>(self, mut track: F) -> Result<MessageId, Error> {
+        let message_id = self.send()?;
+
+        track(message_id);
+
+        Ok(message_id)
+    }
+
+    /// ## Send the message only if a condition holds
+    /// The `cond` closure is evaluated before building the message, if it returns `true` the message is
+    /// sent and its id is returned, otherwise nothing is sent and it returns `Ok(None)`
+    pub fn send_if<F: FnOnce() -> bool>(self, cond: F) -> Result<Option<MessageId>, Error> {
+        if !cond() {
+            return Ok(None);
+        }
+
+        self.send().map(Some)
+    }
+
+    /// ## Send the message only if the destination is not the zero address
+    /// Sending value to the zero address will burn it, this method will return
+    /// `BuilderError::ZeroAddress` instead of sending the message (and `BuilderError::MissingDestination`,
+    /// `BuilderError::IncompleteRoute`, `BuilderError::PayloadTooLarge` or `BuilderError::PayloadLenMismatch`
+    /// if the other checks fail).
+    ///
+    /// It is the only send method that supports `with_treasury_fee`: it returns
+    /// `BuilderError::FeeExceedsValue` or `BuilderError::InsufficientValue` before sending anything, then
+    /// sends the message with the remaining value and after it the fee. If the fee transfer fails, the
+    /// message is already queued, but messages are only dispatched if the execution ends successfully, so
+    /// panic (for example, with `funcs::panicking`) to cancel both
+    pub fn send_checked(mut self) -> Result<MessageId, BuilderError> {
+        let to = self.try_check_data()?;
+
+        let request = self.try_get_request()?;
+        let value = self.value_after_treasury_fee()?;
+
+        let message_id = self.send_request(to, request, value)?;
+
+        if let Some((treasury, fee)) = self.treasury_fee {
+            msg::send_bytes(treasury, [], fee)
+                .map_err(Error::Core)?;
+        }
+
+        Ok(message_id)
+    }
+
+    /// ## Send the message and return the gas consumed
+    /// Returns the message id and the gas consumed by the send, measured with `gas_available()` before
+    /// building the request and after sending it. The measure includes the request encoding, and does not
+    /// include the gas spent by the destination to handle the message.
+    pub fn send_profiled(self) -> Result<(MessageId, u64), Error> {
+        let gas_before = funcs::gas_available();
+
+        let message_id = self.send()?;
+
+        let gas_consumed = gas_before.saturating_sub(funcs::gas_available());
+
+        Ok((message_id, gas_consumed))
+    }
+
+    /// ## Send the message to several recipients, each one with its own value
+    /// The same request (service, method and arguments) is sent to each recipient with the value
+    /// specified for it, the destination and value set in the builder are ignored.
+    ///
+    /// Before sending any message, it checks that the sum of the values does not exceed `value_available()`,
+    /// returning `BuilderError::InsufficientValue` otherwise (and `BuilderError::IncompleteRoute`,
+    /// `BuilderError::PayloadTooLarge` or `BuilderError::PayloadLenMismatch` if the other checks fail).
+    /// The result of each send is returned in the same order as the recipients.
+    pub fn distribute(mut self, recipients: &[(ActorId, u128)]) -> Result<Vec<Result<MessageId, Error>>, BuilderError> {
+        self.try_check_route()?;
+        self.check_no_treasury_fee();
+
+        let required = recipients
+            .iter()
+            .fold(0u128, |total, (_, value)| total.saturating_add(*value));
+        let available = funcs::value_available();
+
+        if required > available {
+            return Err(BuilderError::InsufficientValue { required, available });
+        }
+
+        let request = self.try_get_request()?;
+
+        let results = recipients
+            .iter()
+            .map(|(recipient, value)| self.send_request(*recipient, request.clone(), *value))
+            .collect();
+
+        Ok(results)
+    }
+
+    /// ## Reply to the current message, or send a message to its source
+    /// If the current message can be replied (it is not a reply itself, `msg::reply_to()` fails), it
+    /// replies with the request using `msg::reply_bytes`. Otherwise (for example, in `handle_reply`)
+    /// a reply is not possible, so it sends a new message to `msg::source()`.
+    ///
+    /// The destination set in the builder is ignored.
+    pub fn respond(mut self) -> Result<MessageId, Error> {
+        self.check_route();
+        self.check_no_treasury_fee();
+
+        let request = self.get_request();
+
+        if msg::reply_to().is_ok() {
+            return self.send_request(msg::source(), request, self.value);
+        }
+
+        let result = match self.gas_for_request(&request) {
+            Some(gas) => msg::reply_bytes_with_gas(request, gas, self.value),
+            None => msg::reply_bytes(request, self.value),
+        };
+
+        result.map_err(Error::Core)
+    }
+
+    /// ## Send the message from a reservation, or with explicit gas
+    /// If a `reservation` is provided, the message is sent using its gas (and `gas` is ignored), otherwise
+    /// it is sent with the given `gas`. It is the same logic of the `send_delayed_msg!` macro for
+    /// immediate messages
+    pub fn send_with_gas_or_reservation(mut self, gas: u64, reservation: Option<ReservationId>) -> Result<MessageId, Error> {
+        self.check_data();
+
+        let request = self.get_request();
+
+        let result = if let Some(reservation_id) = reservation {
+            msg::send_bytes_from_reservation(reservation_id, self.to.unwrap(), request, self.value)
+        } else {
+            msg::send_bytes_with_gas(self.to.unwrap(), request, gas, self.value)
+        };
+
+        result.map_err(Error::Core)
     }
 
     /// ## Send a delayed message
@@ -121,11 +670,13 @@ impl MessageBuilder {
 
         let request = self.get_request();
 
-        let message_id = if self.delayed_with_gas.is_some() {
+        let gas = self.delayed_with_gas.or(self.gas_for_request(&request));
+
+        let message_id = if let Some(gas) = gas {
             msg::send_bytes_with_gas_delayed(
                 self.to.unwrap(), 
                 request, 
-                self.delayed_with_gas.unwrap(), 
+                gas, 
                 self.value, 
             self.delayed_duration
             )?
@@ -143,6 +694,33 @@ impl MessageBuilder {
         Ok(message_id)
     }
 
+    /// ## Send a delayed message and return a handle to track it
+    /// Same as `send_delayed`, but it returns a `DelayedHandle` with the message id, the destination
+    /// and the block at which the message will be executed (current block + delay), that the contract
+    /// can store to correlate the message later
+    pub fn send_delayed_tracked(self) -> Result<DelayedHandle, Error> {
+        let to = self.to;
+        let fire_block = exec::block_height().saturating_add(self.delayed_duration);
+
+        let message_id = self.send_delayed()?;
+
+        Ok(DelayedHandle {
+            message_id,
+            to: to.unwrap(),
+            fire_block,
+        })
+    }
+
+    /// ## Send a delayed message with a guard nonce
+    /// Delayed messages are executed unconditionally, this method adds the `guard_nonce` as the last
+    /// argument of the message, so the receiving method (that needs a last `u64` argument) can compare it
+    /// with the nonce stored in its state using `check_guard`, and ignore the message if it is stale.
+    ///
+    /// To "cancel" the delayed message, the contract just needs to change its stored nonce.
+    pub fn send_delayed_guarded(self, guard_nonce: u64) -> Result<MessageId, Error> {
+        self.add_arg(guard_nonce).send_delayed()
+    }
+
     /// ## Send a delayed message with reservation
     pub fn send_delayed_with_reservation(mut self, reservation_id: ReservationId) -> Result<MessageId, Error> {
         self.check_data();
@@ -159,6 +737,34 @@ impl MessageBuilder {
         Ok(result)
     }
 
+    /// ## Send a delayed message to this contract if the gas is low
+    /// If `gas_available()` is below `threshold`, it sends a delayed message to the current
+    /// program (with the configured service, method and arguments) after `blocks`, and returns
+    /// `true` meaning that the execution yielded and should stop. Otherwise it returns `false`
+    /// and nothing is sent.
+    ///
+    /// Useful to process big tasks in several blocks.
+    pub fn continue_if_low_gas(mut self, threshold: u64, blocks: u32) -> Result<bool, Error> {
+        if funcs::gas_available() >= threshold {
+            return Ok(false);
+        }
+
+        self.to = Some(exec::program_id());
+        self.delayed_duration = blocks;
+
+        self.send_delayed()?;
+
+        Ok(true)
+    }
+
+    /// ## Send a delayed message from a reservation after the given blocks
+    /// Sets the delay and sends the message in one step, using `send_bytes_delayed_from_reservation`
+    /// with the given `blocks` and `reservation_id`
+    pub fn delayed_from_reservation(self, blocks: u32, reservation_id: ReservationId) -> Result<MessageId, Error> {
+        self.delay_in_blocks(blocks)
+            .send_delayed_with_reservation(reservation_id)
+    }
+
     /// ## Send a message and waits for the response
     /// This method will send the message to the destination, you have to set the type of the response, because
     /// it will decode the response and return it tou you.
@@ -167,17 +773,225 @@ impl MessageBuilder {
 
         let request = self.get_request();
 
-        let call = msg::send_bytes_for_reply_as::<_, (String, String, R)>(
-            self.to.unwrap(), 
-            request, 
-            self.value, 
-            0
-        )?;
+        self.send_request_for_reply(self.to.unwrap(), request).await
+    }
+
+    /// ## Send a message and waits for the response, with a fallback program
+    /// Same as `send_recv`, but if the call to the destination fails, it sends the same request (the
+    /// payload is reused unchanged) to the `fallback` program and returns its response.
+    ///
+    /// It only retries when the destination did not handle the call: the message could not be sent
+    /// (`Error::Core`) or the reply is an error reply (`Error::ErrorReply`, the execution failed and its
+    /// changes and value were reverted). Other errors, like a timeout or a reply that can't be decoded,
+    /// are returned without retrying, since the destination may have already handled the call, and
+    /// retrying would duplicate its effects and the value sent
+    pub async fn send_recv_with_fallback<R: Decode>(mut self, fallback: ActorId) -> Result<R, Error> {
+        self.check_data();
+
+        let request = self.get_request();
+
+        let reply = match self.send_request_for_reply_future(self.to.unwrap(), request.clone()) {
+            Ok(future) => future.await,
+            Err(error) => Err(error),
+        };
+
+        match reply {
+            Ok(reply) => funcs::decode_sails_reply::<R>(&reply),
+            Err(Error::Core(_) | Error::ErrorReply(..)) => self.send_request_for_reply(fallback, request).await,
+            Err(error) => Err(error),
+        }
+    }
+
+    /// ## Send the same message to several programs and waits for all the responses
+    /// The request is built once and sent to each target (the destination set in the builder is ignored),
+    /// all the messages are sent before awaiting the replies, so the targets handle them concurrently.
+    /// Returns one result per target, in the same order of `targets`
+    pub async fn send_recv_many<R: Decode>(mut self, targets: &[ActorId]) -> Vec<Result<R, Error>> {
+        self.check_route();
+        self.check_no_treasury_fee();
+
+        let request = self.get_request();
+
+        let futures: Vec<_> = targets
+            .iter()
+            .map(|target| self.send_request_for_reply_future(*target, request.clone()))
+            .collect();
+
+        let mut results = Vec::with_capacity(futures.len());
 
-        let call_result = call
+        for future in futures {
+            let result = match future {
+                Ok(future) => future
+                    .await
+                    .and_then(|reply| funcs::decode_sails_reply::<R>(&reply)),
+                Err(error) => Err(error),
+            };
+
+            results.push(result);
+        }
+
+        results
+    }
+
+    /// ## Send a message for reply without waiting for it
+    /// Returns the future of the reply, that resolves to the reply bytes (with the sails format), so you
+    /// can send several messages before awaiting them (see `MessageGroup`). Use `decode_sails_reply` to
+    /// decode the reply
+    pub fn send_recv_future(mut self) -> Result<MessageFuture, Error> {
+        self.check_data();
+
+        let request = self.get_request();
+
+        self.send_request_for_reply_future(self.to.unwrap(), request)
+    }
+
+    /// ## Send a message for reply and return a record to match the reply later
+    /// The message is sent without holding a future, so the reply is not awaited in this execution, it is
+    /// handled in `handle_reply`. The returned `PendingReply` has the message id, the `reply_tag` (chosen
+    /// by the caller to know which type to decode, like the method name) and the block at which it was
+    /// sent, that the contract can store and match with `msg::reply_to()` when the reply arrives (then
+    /// use `decode_sails_reply` to decode it).
+    ///
+    /// The reply deposit set with `with_auto_reply_deposit` is used to pay the `handle_reply` execution
+    pub fn send_recv_deferred(mut self, reply_tag: &str) -> Result<(MessageId, PendingReply), Error> {
+        self.check_data();
+
+        let request = self.get_request();
+
+        let message_id = self.send_request(self.to.unwrap(), request, self.value)?;
+
+        if self.reply_deposit > 0 {
+            exec::reply_deposit(message_id, self.reply_deposit)?;
+        }
+
+        let pending_reply = PendingReply {
+            message_id,
+            reply_tag: String::from(reply_tag),
+            sent_block: exec::block_height(),
+        };
+
+        Ok((message_id, pending_reply))
+    }
+
+    /// ## Send a message and waits for a `Result` response
+    /// Sails services often return a `Result<T, E>`, this method decodes the response as a `Result<T, E>`
+    /// and returns it inside the send result, so the errors while sending the message (outer result) and
+    /// the errors returned by the contract (inner result) can be handled separately.
+    pub async fn send_recv_result<T: Decode, E: Decode>(self) -> Result<Result<T, E>, Error> {
+        self.send_recv::<Result<T, E>>().await
+    }
+
+    /// ## Send a message and waits for the response, panicking on error
+    /// Same as `send_recv`, but if the send or the reply fails, it panics with `funcs::panic`, useful when
+    /// a failed call is fatal for the contract. Since the error is detected when calculating the gas,
+    /// it helps to save on gas fees.
+    pub async fn send_recv_or_panic<R: Decode>(self) -> R {
+        match self.send_recv::<R>().await {
+            Ok(response) => response,
+            Err(error) => funcs::panic(error),
+        }
+    }
+
+    /// ## Send a message and waits for the response, mapping the error
+    /// Same as `send_recv`, but the error is converted with the given closure, so you can return
+    /// your own error type without a `map_err` in each call
+    pub async fn send_recv_map_err<R, E, F>(self, f: F) -> Result<R, E>
+    where
+        R: Decode,
+        F: FnOnce(Error) -> E,
+    {
+        self.send_recv::<R>().await.map_err(f)
+    }
+
+    /// ## Send a message and measure the blocks until the reply
+    /// Sends the message with `send_recv` (ignoring the reply value) and returns the number of blocks
+    /// elapsed between the send and the reply, useful as a health check of the destination. Set the
+    /// service and method of a cheap method of the destination (like a query).
+    pub async fn ping(self) -> Result<u64, Error> {
+        let block_before = exec::block_height();
+
+        self.send_recv::<()>().await?;
+
+        let blocks_elapsed = exec::block_height().saturating_sub(block_before);
+
+        Ok(blocks_elapsed as u64)
+    }
+
+    async fn send_request_for_reply<R: Decode>(&self, to: ActorId, request: Vec<u8>) -> Result<R, Error> {
+        let reply = self
+            .send_request_for_reply_future(to, request)?
             .await?;
 
-        Ok(call_result.2)
+        funcs::decode_sails_reply::<R>(&reply)
+    }
+
+    fn send_request_for_reply_future(&self, to: ActorId, request: Vec<u8>) -> Result<MessageFuture, Error> {
+        let future = match self.gas_for_request(&request) {
+            Some(gas) => msg::send_bytes_with_gas_for_reply(to, request, gas, self.value, self.reply_deposit)?,
+            None => msg::send_bytes_for_reply(to, request, self.value, self.reply_deposit)?,
+        };
+
+        future.up_to(self.reply_deadline)
+    }
+
+    fn send_request(&self, to: ActorId, request: Vec<u8>, value: u128) -> Result<MessageId, Error> {
+        let result = match self.gas_for_request(&request) {
+            Some(gas) => msg::send_bytes_with_gas(to, request, gas, value),
+            None => msg::send_bytes(to, request, value),
+        };
+
+        result.map_err(Error::Core)
+    }
+
+    fn snapshot(&self, request: &[u8], block: u32) -> BuilderSnapshot {
+        BuilderSnapshot {
+            to: self.to.unwrap(),
+            service_name: self.service_name,
+            method_name: self.method_name,
+            request: request.to_vec(),
+            value: self.value,
+            block,
+        }
+    }
+
+    fn gas_for_request(&self, request: &[u8]) -> Option<u64> {
+        self.gas_for_payload
+            .map(|(base, per_byte)| base.saturating_add(per_byte.saturating_mul(request.len() as u64)))
+    }
+
+    fn prepend_arg(&mut self, encoded_arg: Vec<u8>) {
+        let arg_len = encoded_arg.len();
+        let payload_vec = self
+            .payload
+            .get_or_insert(vec![]);
+
+        payload_vec.splice(0..0, encoded_arg);
+
+        for (offset, _) in self.lazy_args.iter_mut() {
+            *offset += arg_len;
+        }
+
+        for start in self.arg_starts.iter_mut() {
+            *start += arg_len;
+        }
+    }
+
+    fn value_after_treasury_fee(&self) -> Result<u128, BuilderError> {
+        let Some((_, fee)) = self.treasury_fee else {
+            return Ok(self.value);
+        };
+
+        let Some(remaining) = self.value.checked_sub(fee) else {
+            return Err(BuilderError::FeeExceedsValue { fee, value: self.value });
+        };
+
+        let available = funcs::value_available();
+
+        if self.value > available {
+            return Err(BuilderError::InsufficientValue { required: self.value, available });
+        }
+
+        Ok(remaining)
     }
 
     fn check_data(&self) {
@@ -185,6 +999,51 @@ impl MessageBuilder {
             funcs::panic("Address to send message cant be empty");
         }
 
+        self.check_route();
+        self.check_no_treasury_fee();
+    }
+
+    pub(super) fn try_check_data(&self) -> Result<ActorId, BuilderError> {
+        let Some(to) = self.to else {
+            return Err(BuilderError::MissingDestination);
+        };
+
+        if funcs::is_zero(to) {
+            return Err(BuilderError::ZeroAddress);
+        }
+
+        self.try_check_route()?;
+
+        Ok(to)
+    }
+
+    fn try_check_route(&self) -> Result<(), BuilderError> {
+        if self.service_name.is_some() != self.method_name.is_some() {
+            return Err(BuilderError::IncompleteRoute);
+        }
+
+        Ok(())
+    }
+
+    fn check_no_treasury_fee(&self) {
+        if self.treasury_fee.is_some() {
+            funcs::panic("The treasury fee is only supported by send_checked");
+        }
+    }
+
+    fn check_payload(&self, len: usize) -> Result<(), BuilderError> {
+        if let Some(max) = self.max_payload && len > max {
+            return Err(BuilderError::PayloadTooLarge { len, max });
+        }
+
+        if let Some(expected) = self.expected_payload_len && len != expected {
+            return Err(BuilderError::PayloadLenMismatch { len, expected });
+        }
+
+        Ok(())
+    }
+
+    fn check_route(&self) {
         let sails_check_1 = self.service_name.is_some() && self.method_name.is_none();
         let sails_check_2 = self.service_name.is_none() && self.method_name.is_some();
 
@@ -193,14 +1052,56 @@ impl MessageBuilder {
         }
     }
 
+    fn build_lazy_args(&mut self) {
+        if self.lazy_args.is_empty() {
+            return;
+        }
+
+        let payload_vec = self
+            .payload
+            .get_or_insert(vec![]);
+
+        let mut shift = 0;
+        for (offset, arg) in self.lazy_args.drain(..) {
+            let encoded_arg = arg();
+            let arg_len = encoded_arg.len();
+            let start = offset + shift;
+
+            payload_vec.splice(start..start, encoded_arg);
+
+            let index = self.arg_starts.partition_point(|arg_start| *arg_start < start);
+            for arg_start in self.arg_starts[index..].iter_mut() {
+                *arg_start += arg_len;
+            }
+
+            self.arg_starts.insert(index, start);
+            shift += arg_len;
+        }
+    }
+
     fn get_request(&mut self) -> Vec<u8> {
+        match self.try_get_request() {
+            Ok(request) => request,
+            Err(error) => funcs::panic(error),
+        }
+    }
 
-        
-        let payload = if self.payload.is_some() {
-            self.payload.take().unwrap()
-        } else {
-            ().encode()
-        };
+    fn try_get_request(&mut self) -> Result<Vec<u8>, BuilderError> {
+        self.build_lazy_args();
+
+        let mut payload = self.payload.take().unwrap_or_default();
+
+        if self.compact {
+            while let Some(start) = self.arg_starts.pop() {
+                if payload[start..] != [0] {
+                    break;
+                }
+
+                payload.truncate(start);
+            }
+        }
+
+        self.check_payload(payload.len())?;
 
         let contract_payload = self.service_name.is_some() && self.method_name.is_some();
 
@@ -214,6 +1115,379 @@ impl MessageBuilder {
             payload
         };
 
-        request
+        Ok(request)
+    }
+}
+
+impl Default for MessageBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+    use gstd::rc::Rc;
+
+    fn request(mut builder: MessageBuilder) -> Vec<u8> {
+        builder.get_request()
+    }
+
+    #[test]
+    fn compact_drops_trailing_empty_args() {
+        let full = request(MessageBuilder::new().add_arg(5u32).add_arg(None::<u8>).add_arg(String::new()));
+        let compact = request(MessageBuilder::new().add_arg(5u32).add_arg(None::<u8>).add_arg(String::new()).compact());
+
+        assert_eq!(full, (5u32, None::<u8>, String::new()).encode());
+        assert_eq!(compact, 5u32.encode());
+        assert!(compact.len() < full.len());
+    }
+
+    #[test]
+    fn compact_keeps_empty_args_before_a_value() {
+        let compact = request(MessageBuilder::new().add_arg(None::<u8>).add_arg(7u32).add_arg(false).compact());
+
+        assert_eq!(compact, (None::<u8>, 7u32).encode());
+    }
+
+    #[test]
+    fn compact_keeps_empty_args_before_a_lazy_arg() {
+        let compact = request(MessageBuilder::new().add_arg(None::<u8>).add_arg_with(|| 9u32).compact());
+
+        assert_eq!(compact, (None::<u8>, 9u32).encode());
+    }
+
+    #[test]
+    fn compact_drops_trailing_empty_lazy_args() {
+        let compact = request(
+            MessageBuilder::new()
+                .add_arg(1u8)
+                .add_arg_with(|| None::<u8>)
+                .add_arg(None::<u8>)
+                .compact()
+        );
+
+        assert_eq!(compact, 1u8.encode());
+    }
+
+    #[test]
+    fn compact_keeps_prepended_args() {
+        let compact = request(MessageBuilder::new().add_arg(None::<u8>).with_schema_version(0).compact());
+
+        assert_eq!(compact, 0u8.encode());
+
+        let compact = request(MessageBuilder::new().add_arg(None::<u8>).with_nonce(3).compact());
+
+        assert_eq!(compact, 3u64.encode());
+    }
+
+    #[test]
+    fn compact_drops_trailing_empty_fields() {
+        let fields = [("amount", 10u64.encode()), ("memo", None::<u8>.encode())];
+        let compact = request(MessageBuilder::new().add_fields(&fields).compact());
+
+        assert_eq!(compact, 10u64.encode());
+    }
+
+    #[test]
+    fn lazy_args_keep_their_position() {
+        let full = request(
+            MessageBuilder::new()
+                .add_arg(1u8)
+                .add_arg_with(|| 2u16)
+                .add_arg(3u32)
+                .add_arg_with(|| 4u64)
+                .with_nonce(5)
+        );
+
+        assert_eq!(full, (5u64, 1u8, 2u16, 3u32, 4u64).encode());
+    }
+
+    #[test]
+    fn max_payload_includes_lazy_args() {
+        let mut builder = MessageBuilder::new().add_arg(1u32).add_arg_with(|| [0u8; 8]).with_max_payload(8);
+
+        assert_eq!(builder.payload_len(), 4);
+        assert!(matches!(builder.try_get_request(), Err(BuilderError::PayloadTooLarge { len: 12, max: 8 })));
+
+        let mut builder = MessageBuilder::new().add_arg(1u32).add_arg_with(|| [0u8; 8]).with_max_payload(12);
+
+        assert_eq!(builder.try_get_request().unwrap(), (1u32, [0u8; 8]).encode());
+    }
+
+    #[test]
+    fn max_payload_is_checked_after_compact() {
+        let mut builder = MessageBuilder::new()
+            .add_arg(1u32)
+            .add_arg(None::<u8>)
+            .with_max_payload(4)
+            .compact();
+
+        assert_eq!(builder.try_get_request().unwrap(), 1u32.encode());
+    }
+
+    #[test]
+    fn expected_payload_len_includes_lazy_args() {
+        let mut builder = MessageBuilder::new().add_arg(1u32).add_arg_with(|| 2u32).expect_payload_len(4);
+
+        assert!(matches!(builder.try_get_request(), Err(BuilderError::PayloadLenMismatch { len: 8, expected: 4 })));
+
+        let mut builder = MessageBuilder::new()
+            .service_name("Service")
+            .method_name("Method")
+            .add_arg(1u32)
+            .add_arg_with(|| 2u32)
+            .expect_payload_len(8);
+
+        assert_eq!(builder.try_get_request().unwrap(), ("Service", "Method", 1u32, 2u32).encode());
+    }
+
+    #[test]
+    fn u64_byte_orders() {
+        let value = 0x0102_0304_0506_0708u64;
+
+        assert_eq!(request(MessageBuilder::new().add_u64(value)), value.to_le_bytes());
+        assert_eq!(request(MessageBuilder::new().add_u64_le(value)), value.to_le_bytes());
+        assert_eq!(request(MessageBuilder::new().add_u64_be_bytes(value)), value.to_be_bytes());
+    }
+
+    #[test]
+    fn expect_decodable_checks_the_args() {
+        let builder = || MessageBuilder::new().service_name("Service").method_name("Method").add_arg(1u32).add_arg_with(|| true);
+
+        assert!(builder().expect_decodable::<(u32, bool)>().is_ok());
+        assert!(matches!(builder().expect_decodable::<(u32, bool, u8)>(), Err(Error::Decode(_))));
+        assert!(matches!(builder().expect_decodable::<u32>(), Err(Error::Decode(_))));
+    }
+
+    #[test]
+    fn ensure_contract_destination_requires_the_mark() {
+        let destination = ActorId::from(1u64);
+
+        assert!(matches!(
+            MessageBuilder::new().ensure_contract_destination(),
+            Err(BuilderError::MissingDestination)
+        ));
+        assert!(matches!(
+            MessageBuilder::new().send_to(destination).ensure_contract_destination(),
+            Err(BuilderError::UnverifiedContractDestination)
+        ));
+        assert!(
+            MessageBuilder::new()
+                .send_to(destination)
+                .assume_contract_destination()
+                .ensure_contract_destination()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn validate_only_outcomes() {
+        let destination = ActorId::from(1u64);
+
+        assert!(matches!(MessageBuilder::new().validate_only(), Err(BuilderError::MissingDestination)));
+        assert!(matches!(
+            MessageBuilder::new().send_to(ActorId::zero()).validate_only(),
+            Err(BuilderError::ZeroAddress)
+        ));
+        assert!(matches!(
+            MessageBuilder::new().send_to(destination).service_name("Service").validate_only(),
+            Err(BuilderError::IncompleteRoute)
+        ));
+        assert!(matches!(
+            MessageBuilder::new().send_to(destination).method_name("Method").validate_only(),
+            Err(BuilderError::IncompleteRoute)
+        ));
+        assert!(matches!(
+            MessageBuilder::new().send_to(destination).add_arg(1u64).with_max_payload(4).validate_only(),
+            Err(BuilderError::PayloadTooLarge { len: 8, max: 4 })
+        ));
+        assert!(matches!(
+            MessageBuilder::new().send_to(destination).add_arg(1u64).expect_payload_len(4).validate_only(),
+            Err(BuilderError::PayloadLenMismatch { len: 8, expected: 4 })
+        ));
+        assert!(matches!(
+            MessageBuilder::new()
+                .send_to(destination)
+                .with_value(10)
+                .with_treasury_fee(ActorId::from(2u64), 11)
+                .validate_only(),
+            Err(BuilderError::FeeExceedsValue { fee: 11, value: 10 })
+        ));
+    }
+
+    #[test]
+    fn send_checked_rejects_an_incomplete_route() {
+        let builder = MessageBuilder::new().send_to(ActorId::from(1u64)).service_name("Service");
+
+        assert!(matches!(builder.send_checked(), Err(BuilderError::IncompleteRoute)));
+    }
+
+    #[test]
+    fn distribute_rejects_an_incomplete_route() {
+        let builder = MessageBuilder::new().method_name("Method");
+
+        assert!(matches!(builder.distribute(&[(ActorId::from(1u64), 0)]), Err(BuilderError::IncompleteRoute)));
+    }
+
+    #[test]
+    fn send_checked_rejects_the_zero_address() {
+        let builder = MessageBuilder::new().send_to(ActorId::zero()).with_value(ONE_TOKEN);
+
+        assert!(matches!(builder.send_checked(), Err(BuilderError::ZeroAddress)));
+    }
+
+    #[test]
+    fn with_tokens_scales_by_one_token() {
+        let builder = MessageBuilder::new().with_tokens(5);
+
+        assert_eq!(builder.value, 5 * ONE_TOKEN);
+    }
+
+    #[test]
+    fn with_tokens_accepts_the_largest_amount() {
+        let whole = u128::MAX / ONE_TOKEN;
+        let builder = MessageBuilder::new().with_tokens(whole);
+
+        assert_eq!(builder.value, whole * ONE_TOKEN);
+        assert!((whole + 1).checked_mul(ONE_TOKEN).is_none());
+    }
+
+    #[test]
+    fn reply_timeout_ms_rounds_up_to_blocks() {
+        let deadline = |ms| MessageBuilder::new().reply_timeout_ms(ms).reply_deadline;
+
+        assert_eq!(deadline(0), Some(1));
+        assert_eq!(deadline(1), Some(1));
+        assert_eq!(deadline(3_000), Some(1));
+        assert_eq!(deadline(3_001), Some(2));
+        assert_eq!(MessageBuilder::new().reply_deadline_blocks(0).reply_deadline, Some(1));
+    }
+
+    #[test]
+    fn lazy_args_are_built_once() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+
+        let builder = MessageBuilder::new().add_arg_with(move || {
+            counter.set(counter.get() + 1);
+
+            7u32
+        });
+
+        assert_eq!(calls.get(), 0);
+        assert_eq!(request(builder), 7u32.encode());
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn idempotency_key_is_the_first_argument() {
+        let key = [7u8; 32];
+        let request = request(MessageBuilder::new().add_arg(1u32).with_idempotency_key(key).add_arg(2u32));
+
+        assert_eq!(request[..32], key);
+        assert_eq!(request[32..], (1u32, 2u32).encode());
+    }
+
+    #[test]
+    fn audit_snapshot_records_the_message() {
+        let destination = ActorId::from(1u64);
+        let mut builder = MessageBuilder::new()
+            .send_to(destination)
+            .service_name("Service")
+            .method_name("Method")
+            .add_arg(5u32)
+            .with_value(ONE_TOKEN);
+
+        let request = builder.get_request();
+        let snapshot = builder.snapshot(&request, 42);
+
+        assert_eq!(snapshot, BuilderSnapshot {
+            to: destination,
+            service_name: Some("Service"),
+            method_name: Some("Method"),
+            request: ("Service", "Method", 5u32).encode(),
+            value: ONE_TOKEN,
+            block: 42,
+        });
+    }
+
+    #[test]
+    fn meta_round_trips_with_the_payload() {
+        let meta = (String::from("origin"), 3u8);
+        let request = request(MessageBuilder::new().add_arg(10u64).with_meta(meta.clone()).add_arg(true));
+
+        let (decoded, remaining) = funcs::decode_meta::<(String, u8)>(&request).unwrap();
+
+        assert_eq!(decoded, meta);
+        assert_eq!(remaining, (10u64, true).encode());
+    }
+
+    #[test]
+    fn value_at_least_applies_the_floor() {
+        assert_eq!(MessageBuilder::new().with_value(5).with_value_at_least(10).value, 10);
+        assert_eq!(MessageBuilder::new().with_value(15).with_value_at_least(10).value, 15);
+    }
+
+    #[test]
+    fn gas_for_payload_charges_the_full_request() {
+        let mut builder = MessageBuilder::new()
+            .service_name("Service")
+            .method_name("Method")
+            .add_arg(5u32)
+            .with_gas_for_payload(1_000, 10);
+
+        let request = builder.get_request();
+
+        assert_eq!(request.len(), ("Service", "Method", 5u32).encode().len());
+        assert_eq!(builder.gas_for_request(&request), Some(1_000 + 10 * request.len() as u64));
+        assert_eq!(MessageBuilder::new().gas_for_request(&request), None);
+    }
+
+    #[test]
+    fn blob_decodes_to_the_original_bytes() {
+        let blob = [1u8, 2, 3, 255];
+        let request = request(MessageBuilder::new().add_blob(&blob));
+
+        assert_eq!(Vec::<u8>::decode(&mut request.as_slice()).unwrap(), blob);
+    }
+
+    #[test]
+    fn auto_reply_deposit_uses_the_reply_estimate() {
+        assert_eq!(MessageBuilder::new().reply_deposit, 0);
+        assert_eq!(MessageBuilder::new().with_auto_reply_deposit().reply_deposit, funcs::estimate_reply_gas());
+    }
+
+    #[test]
+    fn fields_keep_the_slice_order() {
+        let fields = [("a", 1u8.encode()), ("b", 2u16.encode()), ("c", String::from("c").encode())];
+        let request = request(MessageBuilder::new().add_arg(0u8).add_fields(&fields));
+
+        assert_eq!(request, (0u8, 1u8, 2u16, String::from("c")).encode());
+    }
+
+    #[test]
+    fn deadline_block_is_the_first_argument() {
+        let request = request(
+            MessageBuilder::new()
+                .service_name("Service")
+                .method_name("Method")
+                .add_arg(1u32)
+                .with_deadline_block(1_000)
+        );
+
+        assert_eq!(request, ("Service", "Method", 1_000u64, 1u32).encode());
+    }
+
+    #[test]
+    fn fits_in_gas_around_the_estimate() {
+        let builder = MessageBuilder::new().add_arg([0u8; 64]);
+        let estimate = funcs::estimate_message_gas(builder.payload_len());
+
+        assert!(!builder.fits_in_gas(estimate - 1));
+        assert!(builder.fits_in_gas(estimate));
+        assert!(builder.fits_in_gas(estimate + 1));
     }
 }
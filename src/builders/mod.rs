@@ -1 +1,6 @@
-pub mod message;
\ No newline at end of file
+pub mod message;
+pub mod batch;
+pub mod group;
+pub mod error;
+pub mod snapshot;
+pub mod handle;
\ No newline at end of file
@@ -0,0 +1,20 @@
+use sails_rs::{
+    prelude::*
+};
+
+/// Snapshot of a message sent by the `MessageBuilder`, used for audit trails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuilderSnapshot {
+    /// Destination of the message
+    pub to: ActorId,
+    /// Service name called (if any)
+    pub service_name: Option<&'static str>,
+    /// Method name called (if any)
+    pub method_name: Option<&'static str>,
+    /// Full request sent (service and method names with the arguments)
+    pub request: Vec<u8>,
+    /// Value sent in the message
+    pub value: u128,
+    /// Block in which the message was sent
+    pub block: u32,
+}
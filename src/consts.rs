@@ -14,6 +14,9 @@ pub const TOTAL_BLOCKS_TO_UNBOND: u64 = 201_600;
 /// using block "time", since this way you can know if in the current block
 pub const TOTAL_MILISECONDS_TO_UBOND: u64 = 604_800_000;
 
+/// Expected time in milliseconds that takes to produce one block in Vara Network (≈3 seconds).
+pub const BLOCK_TIME_MS: u64 = 3_000;
+
 /// Number of blocks that make up a single era.
 ///
 /// Since each block takes ~3 seconds, this corresponds to:
@@ -24,6 +27,38 @@ pub const ONE_ERA_IN_BLOCKS: u64 = 14_400;
 /// Milliseconds of one era
 pub const ONE_ERA_IN_MILLISECONDS: u64 = 43_200_000;
 
+/// Milliseconds of one day
+pub const ONE_DAY_IN_MILLISECONDS: u64 = 86_400_000;
+
+/// Milliseconds of one year (365 days)
+pub const ONE_YEAR_IN_MILLISECONDS: u64 = 31_536_000_000;
+
+/// Number of eras in one year (365 days).
+///
+/// 31_536_000_000 ms / 43_200_000 ms = 730 eras per year
+pub const ERAS_PER_YEAR: u64 = ONE_YEAR_IN_MILLISECONDS / ONE_ERA_IN_MILLISECONDS;
+
+/// Number of blocks in one year (365 days), with ≈3 seconds per block.
+///
+/// 730 eras × 14_400 blocks = 10_512_000 blocks per year
+pub const BLOCKS_PER_YEAR: u64 = ERAS_PER_YEAR * ONE_ERA_IN_BLOCKS;
+
+/// Number of eras for which the staking rewards can be claimed in Vara Network.
+///
+/// Rewards of eras older than `active_era - HISTORY_DEPTH` can no longer be claimed.
+pub const HISTORY_DEPTH: u64 = 84;
+
+/// Number of eras that the staked tokens stay bonded after unbonding them.
+///
+/// 14 eras × 12 hours = 7 days, the same as `TOTAL_BLOCKS_TO_UNBOND` (14 × `ONE_ERA_IN_BLOCKS`).
+pub const BONDING_DURATION_ERAS: u64 = 14;
+
+/// Number of eras that a slash is deferred before it is applied.
+///
+/// It is lower than `BONDING_DURATION_ERAS`, so the slash is applied before the slashed tokens
+/// can be unbonded.
+pub const SLASH_DEFER_DURATION_ERAS: u64 = 13;
+
 /// Last active era registered on the testnet used as a reference point.
 ///
 /// Useful for estimating the current era based on block height,
@@ -68,3 +103,26 @@ pub const LAST_MAINNET_ACTIVE_ERA_INIT_TIMESTAMP: u64 = 1_761_264_579_001;
 /// ```
 pub const ONE_TOKEN: u128 = 1e12 as u128;
 
+/// Base gas used to estimate the gas needed to create a program.
+///
+/// Covers the fixed cost of the program creation and the execution of a small `init`.
+pub const CREATE_PROGRAM_BASE_GAS: u64 = 2_000_000_000;
+
+/// Gas added to the program creation estimation for each byte of the program code.
+///
+/// The code needs to be instrumented and stored, so bigger codes need more gas.
+pub const CREATE_PROGRAM_GAS_PER_CODE_BYTE: u64 = 20_000;
+
+/// Gas added to the program creation estimation for each byte of the init payload.
+pub const CREATE_PROGRAM_GAS_PER_PAYLOAD_BYTE: u64 = 5_000;
+
+/// Base gas used to estimate the gas needed to handle a message.
+pub const MESSAGE_BASE_GAS: u64 = 500_000_000;
+
+/// Gas added to the message estimation for each byte of the payload.
+pub const MESSAGE_GAS_PER_PAYLOAD_BYTE: u64 = 5_000;
+
+/// Gas estimated to handle a reply (with a small payload) in `handle_reply`.
+///
+/// Used as the reply deposit when the sender pays for the reply of its message.
+pub const REPLY_DEPOSIT_GAS: u64 = 1_000_000_000;
\ No newline at end of file
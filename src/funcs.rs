@@ -1,8 +1,14 @@
-use core::fmt::Debug;
-use gstd::{exec, ext, format};
+use core::{fmt::Debug, ops::RangeInclusive};
+use gstd::{exec, ext, format, collections::BTreeMap, errors::Error, ActorId};
+use sails_rs::prelude::*;
 use crate::{
-    builders::message::MessageBuilder,
-    consts::*
+    builders::{
+        message::MessageBuilder,
+        batch::MessageBatch,
+        group::MessageGroup
+    },
+    consts::*,
+    network::Network
 };
 
 /// # Create a new MessageBuilder
@@ -10,77 +16,1385 @@ pub fn new_message() -> MessageBuilder {
     MessageBuilder::new()
 }
 
+/// # Create a new MessageBatch
+pub fn new_batch() -> MessageBatch {
+    MessageBatch::new()
+}
+
+/// # Create a new MessageGroup
+pub fn new_group() -> MessageGroup {
+    MessageGroup::new()
+}
+
 /// Executes a closure, panicking if it returns an error.
 ///
 /// This helper is useful in tests or internal logic where failure is not recoverable,
 /// and you want to surface the error immediately with a clear panic.
 ///
-/// # Type Parameters
-/// - `T`: The success type returned by the closure.
-/// - `E`: The error type (must implement `Debug`).
+/// # Type Parameters
+/// - `T`: The success type returned by the closure.
+/// - `E`: The error type (must implement `Debug`).
+///
+/// # Arguments
+/// - `f`: A closure returning `Result<T, E>`.
+///
+/// # Panics
+/// Panics with the error if `f()` returns `Err`.
+///
+/// # Example
+/// ```
+/// let result = panicking(|| Ok(42)); // returns 42
+/// let result = panicking(|| Err("failure")); // panics with "failure"
+/// ```
+pub fn panicking<T, E: Debug, F: FnOnce() -> Result<T, E>>(f: F) -> T {
+    match f() {
+        Ok(v) => v,
+        Err(e) => panic(e),
+    }
+}
+
+/// Terminates execution with a panic, logging the debug representation of the provided error.
+///
+/// This is a wrapper around `gstd::ext::panic` that formats the error for clarity.
+/// It is suitable for terminating execution inside smart contracts when a fatal error occurs.
+/// 
+/// This helps save on gas fees since the error is detected when calculating the total amount of gas used.
+///
+/// # Arguments
+/// - `err`: Any value implementing `Debug`, used for formatting the panic message.
+///
+/// # Behavior
+/// Calls `ext::panic` with a `format!("{err:?}")` message and does **not** return.
+///
+/// # Example
+/// ```
+/// panic("unrecoverable failure");
+/// panic(my_enum_error);
+/// ```
+pub fn panic(err: impl Debug) -> ! {
+    ext::panic(format!("{err:?}"))
+}
+
+/// Gas and value available in the current execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemainingBudget {
+    pub gas: u64,
+    pub value: u128,
+}
+
+/// Returns the gas available in the current execution (`exec::gas_available`).
+pub fn gas_available() -> u64 {
+    exec::gas_available()
+}
+
+/// Returns the value (balance) available for the current program (`exec::value_available`).
+pub fn value_available() -> u128 {
+    exec::value_available()
+}
+
+/// Returns the gas and value available in the current execution.
+pub fn remaining_budget() -> RemainingBudget {
+    RemainingBudget {
+        gas: gas_available(),
+        value: value_available(),
+    }
+}
+
+/// Checks if the given address is the zero address.
+///
+/// Sending value to the zero address will burn it, so it is useful to check it before sending a message.
+pub fn is_zero(address: ActorId) -> bool {
+    address == ActorId::zero()
+}
+
+/// Calculates the fee to deduct from an amount, given the fee in permille (1 permille = 0.1%).
+///
+/// The fee is `amount * fee_permille / 1000` rounded down, it is computed without overflowing
+/// for any amount, and it never exceeds the amount (a fee above 1000 permille takes the whole amount).
+///
+/// ### Parameters
+/// - `amount`: Total amount to transfer.
+/// - `fee_permille`: Fee in permille, for example `25` is a 2.5% fee.
+///
+/// ### Returns
+/// A tuple `(net, fee)`, where `net + fee == amount`.
+pub fn apply_fee(amount: u128, fee_permille: u32) -> (u128, u128) {
+    let fee = mul_permille(amount, fee_permille).min(amount);
+
+    (amount - fee, fee)
+}
+
+/// Calculates the share of a reward pool that belongs to a stake (`pool * stake / total_stake`).
+///
+/// The multiplication is done with a 256 bits intermediate, so large values don't overflow nor
+/// lose precision. The result is rounded down.
+///
+/// ### Parameters
+/// - `stake`: Amount staked by the user.
+/// - `total_stake`: Total amount staked in the pool.
+/// - `pool`: Total reward of the pool.
+///
+/// ### Returns
+/// The share of the stake, or 0 if the total stake is zero (saturates at `u128::MAX`).
+pub fn stake_share(stake: u128, total_stake: u128, pool: u128) -> u128 {
+    if total_stake == 0 {
+        return 0;
+    }
+
+    mul_div(pool, stake, total_stake)
+}
+
+/// Computes `amount * permille / 1000` rounded down, without overflowing in the intermediate
+/// multiplication (saturates at `u128::MAX`).
+fn mul_permille(amount: u128, permille: u32) -> u128 {
+    let permille = permille as u128;
+
+    (amount / 1000)
+        .saturating_mul(permille)
+        .saturating_add((amount % 1000) * permille / 1000)
+}
+
+/// Projects the compound growth of a stake after a number of eras.
+///
+/// Each era the reward `amount * rate_permille / 1000` is added to the amount, using integer math.
+///
+/// ### Precision
+/// The reward of each era is rounded down, so the result can be slightly lower than the exact compound
+/// growth (and an amount whose reward rounds to zero will not grow anymore). The result saturates
+/// at `u128::MAX`.
+///
+/// ### Parameters
+/// - `principal`: Initial amount staked.
+/// - `rate_permille`: Reward rate per era in permille.
+/// - `eras`: Number of eras to project.
+pub fn project_rewards(principal: u128, rate_permille: u32, eras: u64) -> u128 {
+    let mut amount = principal;
+
+    for _ in 0..eras {
+        let reward = mul_permille(amount, rate_permille);
+
+        if reward == 0 || amount == u128::MAX {
+            break;
+        }
+
+        amount = amount.saturating_add(reward);
+    }
+
+    amount
+}
+
+/// Projects the compound growth of a stake after a number of eras plus a partial era.
+///
+/// Same as `project_rewards`, but after the whole eras, a fraction of the reward of one more era is
+/// added, proportional to `partial_era_permille` (for example, the current `era_progress_permille`).
+///
+/// ### Precision
+/// The partial reward is `reward * partial_era_permille / 1000`, and each step is rounded down, so
+/// the result can be slightly lower than the exact growth. A `partial_era_permille` above 1000 is
+/// treated as a whole era.
+///
+/// ### Parameters
+/// - `principal`: Initial amount staked.
+/// - `rate_permille`: Reward rate per era in permille.
+/// - `eras`: Number of whole eras to project.
+/// - `partial_era_permille`: Fraction of the last era in permille (0 to 1000).
+pub fn project_rewards_partial(principal: u128, rate_permille: u32, eras: u64, partial_era_permille: u64) -> u128 {
+    let amount = project_rewards(principal, rate_permille, eras);
+    let era_reward = mul_permille(amount, rate_permille);
+    let partial_reward = mul_permille(era_reward, partial_era_permille.min(1000) as u32);
+
+    amount.saturating_add(partial_reward)
+}
+
+/// Calculates the emission of an era after a geometric decay over a number of eras.
+///
+/// Each era the emission is reduced by `emission * decay_permille_per_era / 1000`, using integer math,
+/// saturating at zero (a decay of 1000 permille or more leaves no emission after one era).
+///
+/// ### Precision
+/// The reduction of each era is rounded down, so the result can be slightly higher than the exact
+/// geometric decay, and the error accumulates over many eras. Once the reduction rounds to zero the
+/// emission does not decay anymore (for example, an emission below `1000 / decay_permille_per_era`).
+///
+/// ### Parameters
+/// - `base`: Emission of the first era.
+/// - `decay_permille_per_era`: Decay rate per era in permille.
+/// - `eras_elapsed`: Number of eras elapsed since the first era.
+pub fn decayed_emission(base: u128, decay_permille_per_era: u32, eras_elapsed: u64) -> u128 {
+    let mut emission = base;
+
+    for _ in 0..eras_elapsed {
+        let reduction = mul_permille(emission, decay_permille_per_era);
+
+        if reduction == 0 {
+            break;
+        }
+
+        emission = emission.saturating_sub(reduction);
+    }
+
+    emission
+}
+
+/// Returns the number of eras in one year of 365 days (`ERAS_PER_YEAR`).
+///
+/// Useful to annualize per-era rewards.
+pub fn eras_per_year() -> u64 {
+    ERAS_PER_YEAR
+}
+
+/// Calculates the annual percentage rate (in permille) of a principal from its reward per era.
+///
+/// It is the simple (not compounded) annualized rate: `per_era_reward * ERAS_PER_YEAR * 1000 / principal`,
+/// the inverse of `per_era_reward_from_apy`. The result is rounded down and saturates at `u32::MAX`.
+///
+/// ### Parameters
+/// - `principal`: Amount staked.
+/// - `per_era_reward`: Reward received in one era.
+///
+/// ### Returns
+/// The annual rate in permille, or 0 if the principal is zero.
+pub fn apr_permille_from_per_era(principal: u128, per_era_reward: u128) -> u32 {
+    if principal == 0 {
+        return 0;
+    }
+
+    let annual_reward = per_era_reward.saturating_mul(ERAS_PER_YEAR as u128);
+    let apr_permille = mul_div(annual_reward, 1000, principal);
+
+    apr_permille.min(u32::MAX as u128) as u32
+}
+
+/// Rescales an amount from one number of decimals to another.
+///
+/// Useful to convert between token units (`ONE_TOKEN` has 12 decimals) and external pricing units
+/// (like cents, with 2 decimals). Scaling down rounds down.
+///
+/// ### Parameters
+/// - `amount`: Amount with `from_decimals` decimals.
+/// - `from_decimals`: Decimals of the amount.
+/// - `to_decimals`: Decimals of the result.
+///
+/// ### Returns
+/// The rescaled amount, or `None` if it overflows.
+pub fn convert_units(amount: u128, from_decimals: u8, to_decimals: u8) -> Option<u128> {
+    if to_decimals >= from_decimals {
+        let factor = 10u128.checked_pow((to_decimals - from_decimals) as u32)?;
+        amount.checked_mul(factor)
+    } else {
+        match 10u128.checked_pow((from_decimals - to_decimals) as u32) {
+            Some(factor) => Some(amount / factor),
+            None => Some(0),
+        }
+    }
+}
+
+/// Calculates the fraction of a year (365 days) elapsed since a timestamp, in permille (0 to 1000).
+///
+/// Useful for time-weighted reward accrual, it saturates at 1000 after one year.
+///
+/// ### Parameters
+/// - `timestamp`: Start timestamp in milliseconds.
+pub fn year_fraction_permille_since(timestamp: u64) -> u64 {
+    let ms_passed = ms_passed_since_init_timestamp(timestamp);
+
+    (ms_passed.min(ONE_YEAR_IN_MILLISECONDS) * 1000) / ONE_YEAR_IN_MILLISECONDS
+}
+
+/// Calculates the staking reward per era of a principal, given its annual yield in permille.
+///
+/// The annual reward is split equally between the `ERAS_PER_YEAR` eras (a 365 days year, 730 eras),
+/// without compounding.
+/// The result is rounded down.
+///
+/// ### Parameters
+/// - `principal`: Amount staked.
+/// - `apy_permille`: Annual yield in permille, for example `100` is a 10% yield.
+pub fn per_era_reward_from_apy(principal: u128, apy_permille: u32) -> u128 {
+    mul_permille(principal, apy_permille) / eras_per_year() as u128
+}
+
+/// Calculates the amount slashed from a stake, given the slash in permille (rounded down).
+///
+/// The slash never exceeds the stake.
+///
+/// ### Parameters
+/// - `stake`: Amount staked.
+/// - `slash_permille`: Slash in permille, for example `10` is a 1% slash.
+pub fn slash_amount(stake: u128, slash_permille: u32) -> u128 {
+    mul_permille(stake, slash_permille).min(stake)
+}
+
+/// Calculates the era in which a slash reported in `current_era` is applied.
+///
+/// Slashes are deferred `SLASH_DEFER_DURATION_ERAS` eras before they are applied.
+///
+/// ### Parameters
+/// - `current_era`: Era in which the slash was reported.
+pub fn slash_applies_at_era(current_era: u64) -> u64 {
+    current_era.saturating_add(SLASH_DEFER_DURATION_ERAS)
+}
+
+/// Estimates the gas needed to create a program based on its code and init payload size.
+///
+/// The estimation follows a linear model:
+///
+/// `CREATE_PROGRAM_BASE_GAS + code_len * CREATE_PROGRAM_GAS_PER_CODE_BYTE + init_payload_len * CREATE_PROGRAM_GAS_PER_PAYLOAD_BYTE`
+///
+/// It is an approximation, if the `init` of the program does heavy work you should add more gas.
+///
+/// ### Parameters
+/// - `code_len`: Size in bytes of the program code (wasm).
+/// - `init_payload_len`: Size in bytes of the encoded init payload.
+///
+/// ### Returns
+/// The estimated gas, saturating at `u64::MAX`.
+pub fn estimate_create_program_gas(code_len: usize, init_payload_len: usize) -> u64 {
+    let code_gas = (code_len as u64).saturating_mul(CREATE_PROGRAM_GAS_PER_CODE_BYTE);
+    let payload_gas = (init_payload_len as u64).saturating_mul(CREATE_PROGRAM_GAS_PER_PAYLOAD_BYTE);
+
+    CREATE_PROGRAM_BASE_GAS
+        .saturating_add(code_gas)
+        .saturating_add(payload_gas)
+}
+
+/// Validates the reference constants of both networks.
+///
+/// The reference era constants are updated manually, so they can be misconfigured. You can call
+/// this function in the `init` of your contract to fail fast, see `NetworkConfig::validate` for
+/// the checks done.
+///
+/// ### Returns
+/// `Ok(())` if the constants are valid, otherwise the first error found.
+pub fn validate_reference_constants() -> Result<(), &'static str> {
+    Network::Testnet.config().validate()?;
+    Network::Mainnet.config().validate()
+}
+
+/// Checks if the guard nonce of a delayed message is still valid.
+///
+/// Used in the receiving method of a message sent with `send_delayed_guarded`, if it returns
+/// `false`, the state changed after the message was scheduled and it should be ignored.
+///
+/// ### Parameters
+/// - `nonce`: Guard nonce received in the message.
+/// - `current`: Guard nonce stored in the contract state.
+pub fn check_guard(nonce: u64, current: u64) -> bool {
+    nonce == current
+}
+
+/// Builds a reply with the sails format `(service, method, value)`.
+///
+/// This is the format that `send_recv` decodes, so it is useful for gateway contracts that reply
+/// in behalf of other services, or for mocks of a sails contract.
+///
+/// ### Parameters
+/// - `service`: Service name of the reply.
+/// - `method`: Method name of the reply.
+/// - `value`: Value to reply.
+pub fn build_framed_reply<R: Encode>(service: &str, method: &str, value: &R) -> Vec<u8> {
+    (service, method, value).encode()
+}
+
+/// Decodes a reply with the sails format `(service, method, value)`, returning the value.
+///
+/// ### Parameters
+/// - `bytes`: Reply bytes.
+///
+/// ### Errors
+/// `Error::Decode` if the bytes are not a valid sails reply of type `R`.
+pub fn decode_sails_reply<R: Decode>(bytes: &[u8]) -> Result<R, Error> {
+    let (_, _, value) = <(String, String, R)>::decode(&mut &bytes[..])
+        .map_err(Error::Decode)?;
+
+    Ok(value)
+}
+
+/// Decodes the metadata added with `with_meta` at the start of the arguments of a message.
+///
+/// ### Parameters
+/// - `bytes`: Arguments of the message (without the service and method names).
+///
+/// ### Returns
+/// The decoded metadata and the remaining arguments.
+///
+/// ### Errors
+/// `Error::Decode` if the bytes don't start with a valid `M`.
+pub fn decode_meta<M: Decode>(bytes: &[u8]) -> Result<(M, &[u8]), Error> {
+    let mut remaining = bytes;
+    let meta = M::decode(&mut remaining)
+        .map_err(Error::Decode)?;
+
+    Ok((meta, remaining))
+}
+
+/// Decodes the original sender added with `relay_from` at the start of the arguments of a message.
+///
+/// Only trust the original sender if the relay (`msg::source()`) is a trusted contract.
+///
+/// ### Parameters
+/// - `bytes`: Arguments of the message (without the service and method names).
+///
+/// ### Returns
+/// The original sender and the remaining arguments.
+pub fn decode_relay_sender(bytes: &[u8]) -> Result<(ActorId, &[u8]), Error> {
+    decode_meta::<ActorId>(bytes)
+}
+
+/// Checks if the deadline of a message sent with `with_deadline_block` has passed.
+///
+/// ### Parameters
+/// - `deadline_block`: Deadline received in the message.
+///
+/// ### Returns
+/// `true` if the current block is after the deadline block.
+pub fn is_expired(deadline_block: u64) -> bool {
+    (exec::block_height() as u64) > deadline_block
+}
+
+/// Reads the schema version added with `with_schema_version` at the start of the arguments of a message.
+///
+/// ### Parameters
+/// - `bytes`: Arguments of the message (without the service and method names).
+///
+/// ### Returns
+/// The version and the remaining arguments, or `None` if the bytes are empty.
+pub fn read_schema_version(bytes: &[u8]) -> Option<(u8, &[u8])> {
+    bytes
+        .split_first()
+        .map(|(version, remaining)| (*version, remaining))
+}
+
+/// Extracts the trace id added with `with_trace_id` at the start of the arguments of a message.
+///
+/// ### Parameters
+/// - `bytes`: Arguments of the message (without the service and method names).
+///
+/// ### Returns
+/// The trace id and the remaining arguments, or `None` if there are less than 16 bytes.
+pub fn extract_trace_id(bytes: &[u8]) -> Option<([u8; 16], &[u8])> {
+    let (id, remaining) = bytes.split_first_chunk::<16>()?;
+
+    Some((*id, remaining))
+}
+
+/// Checks the nonce of a message sent with `with_nonce`, and consumes it if it is valid.
+///
+/// The contract stores the next nonce expected for each sender, if the received nonce matches it,
+/// the stored nonce is incremented and `true` is returned, otherwise the message is a replay (or
+/// out of order) and `false` is returned without changing the stored nonce.
+///
+/// ### Parameters
+/// - `nonce`: Nonce received in the message.
+/// - `next_nonce`: Next nonce expected, stored in the contract state.
+pub fn check_and_consume_nonce(nonce: u64, next_nonce: &mut u64) -> bool {
+    if nonce != *next_nonce {
+        return false;
+    }
+
+    *next_nonce = next_nonce.wrapping_add(1);
+
+    true
+}
+
+/// Returns the duration of one era in a human readable format (`"12h 0m"`).
+pub fn era_duration_human() -> String {
+    duration_human(ONE_ERA_IN_MILLISECONDS)
+}
+
+/// Returns the duration of the unbonding period in a human readable format (`"7d 0h 0m"`).
+pub fn unbond_duration_human() -> String {
+    duration_human(TOTAL_MILISECONDS_TO_UBOND)
+}
+
+/// Formats a duration in milliseconds as `"{days}d {hours}h {minutes}m"`, omitting the days if
+/// the duration is shorter than one day.
+fn duration_human(ms: u64) -> String {
+    let total_minutes = ms / 60_000;
+    let minutes = total_minutes % 60;
+    let hours = (total_minutes / 60) % 24;
+    let days = total_minutes / (60 * 24);
+
+    if days > 0 {
+        format!("{days}d {hours}h {minutes}m")
+    } else {
+        format!("{hours}h {minutes}m")
+    }
+}
+
+/// Calculates the progress of an unbonding in permille (0 to 1000), based on blocks.
+///
+/// ### Parameters
+/// - `start_block`: Block at which the unbonding started.
+///
+/// ### Returns
+/// The blocks passed versus `TOTAL_BLOCKS_TO_UNBOND` in permille, saturating at 1000 when completed.
+pub fn unbond_progress_permille(start_block: u64) -> u64 {
+    let blocks_passed = blocks_passed_since_init_block(start_block);
+
+    (blocks_passed.min(TOTAL_BLOCKS_TO_UNBOND) * 1000) / TOTAL_BLOCKS_TO_UNBOND
+}
+
+/// Calculates the progress of an unbonding in permille (0 to 1000), based on timestamps.
+///
+/// ### Parameters
+/// - `start_timestamp`: Timestamp at which the unbonding started.
+///
+/// ### Returns
+/// The milliseconds passed versus `TOTAL_MILISECONDS_TO_UBOND` in permille, saturating at 1000 when completed.
+pub fn unbond_progress_permille_by_timestamp(start_timestamp: u64) -> u64 {
+    let ms_passed = ms_passed_since_init_timestamp(start_timestamp);
+
+    (ms_passed.min(TOTAL_MILISECONDS_TO_UBOND) * 1000) / TOTAL_MILISECONDS_TO_UBOND
+}
+
+/// Calculates how many seconds remain until an unbonding is completed.
+///
+/// ### Parameters
+/// - `start_timestamp`: Timestamp at which the unbonding started.
+///
+/// ### Returns
+/// The milliseconds remaining until `start_timestamp + TOTAL_MILISECONDS_TO_UBOND` divided by 1000
+/// (rounding up), or 0 if the unbonding is completed.
+pub fn seconds_remaining_to_unbond(start_timestamp: u64) -> u64 {
+    let ms_passed = ms_passed_since_init_timestamp(start_timestamp);
+    let ms_remaining = TOTAL_MILISECONDS_TO_UBOND.saturating_sub(ms_passed);
+
+    ms_remaining.div_ceil(1000)
+}
+
+/// Calculates the earliest block at which one of the given unbondings is completed.
+///
+/// Unbondings already completed (`start + TOTAL_BLOCKS_TO_UNBOND <= current block`) are ignored,
+/// useful to schedule a single wakeup for a whole staking pool.
+///
+/// ### Parameters
+/// - `starts`: Blocks at which each unbonding started.
+///
+/// ### Returns
+/// The earliest completion block, or `None` if all the unbondings are completed.
+pub fn next_unbond_completion(starts: &[u64]) -> Option<u64> {
+    next_unbond_completion_at(starts, exec::block_height() as u64)
+}
+
+/// Same as `next_unbond_completion`, but with the given block height instead of the current one.
+///
+/// ### Parameters
+/// - `starts`: Blocks at which each unbonding started.
+/// - `block_height`: Block height used to ignore the completed unbondings.
+pub fn next_unbond_completion_at(starts: &[u64], block_height: u64) -> Option<u64> {
+    starts
+        .iter()
+        .map(|start| start.saturating_add(TOTAL_BLOCKS_TO_UNBOND))
+        .filter(|completion| *completion > block_height)
+        .min()
+}
+
+/// Era based rate limiter, to allow an action once per era.
+///
+/// The contract stores the last era in which each actor did the action (for example, in a
+/// `BTreeMap<ActorId, u64>`), and uses the limiter to check it.
+pub struct RateLimiter;
+
+impl RateLimiter {
+    /// Checks if a new era has begun since `last_era`.
+    ///
+    /// ### Returns
+    /// `Some(current_era)` if the action is allowed (the contract should store it as the new last era),
+    /// otherwise `None`.
+    pub fn allow(last_era: u64, network: Network) -> Option<u64> {
+        RateLimiter::allow_at(last_era, network, exec::block_timestamp())
+    }
+
+    /// Same as `allow`, but the current era is estimated at the given timestamp (in milliseconds)
+    /// instead of the current block timestamp.
+    pub fn allow_at(last_era: u64, network: Network, timestamp: u64) -> Option<u64> {
+        let current_era = era_at_timestamp(network, timestamp);
+
+        (current_era > last_era).then_some(current_era)
+    }
+
+    /// Checks if the actor is allowed to do the action, and records the current era if so.
+    ///
+    /// Actors without a recorded era are always allowed.
+    pub fn allow_and_record(last_eras: &mut BTreeMap<ActorId, u64>, actor: ActorId, network: Network) -> bool {
+        let current_era = match last_eras.get(&actor) {
+            Some(last_era) => RateLimiter::allow(*last_era, network),
+            None => Some(active_era(network)),
+        };
+
+        let Some(current_era) = current_era else {
+            return false;
+        };
+
+        last_eras.insert(actor, current_era);
+
+        true
+    }
+}
+
+/// Calculates how old the reference era timestamps are, relative to the current block timestamp.
+///
+/// The reference constants are updated periodically, and the era estimations get less accurate as
+/// they get older. It uses the oldest reference timestamp between testnet and mainnet.
+///
+/// ### Returns
+/// The age of the oldest reference timestamp in milliseconds.
+pub fn reference_age_ms() -> u64 {
+    let oldest_reference = LAST_TESTNET_ACTIVE_ERA_INIT_TIMESTAMP.min(LAST_MAINNET_ACTIVE_ERA_INIT_TIMESTAMP);
+
+    ms_passed_since_init_timestamp(oldest_reference)
+}
+
+/// Checks if the reference era constants are older than `max_age_ms`.
+///
+/// Contracts can use it to warn or to switch to other era estimations when the reference
+/// constants are too old.
+pub fn reference_is_stale(max_age_ms: u64) -> bool {
+    reference_age_ms() > max_age_ms
+}
+
+/// Estimates the minimum gas needed to handle a message based on its payload size.
+///
+/// The estimation follows a linear model:
+///
+/// `MESSAGE_BASE_GAS + payload_len * MESSAGE_GAS_PER_PAYLOAD_BYTE`
+///
+/// It is a heuristic, it does not include the gas spent by the logic of the destination.
+///
+/// ### Parameters
+/// - `payload_len`: Size in bytes of the payload.
+pub fn estimate_message_gas(payload_len: usize) -> u64 {
+    let payload_gas = (payload_len as u64).saturating_mul(MESSAGE_GAS_PER_PAYLOAD_BYTE);
+
+    MESSAGE_BASE_GAS.saturating_add(payload_gas)
+}
+
+/// Calculates how many delayed messages can be funded with the gas of a reservation.
+///
+/// ### Parameters
+/// - `reservation_gas`: Gas of the reservation.
+/// - `per_message_gas`: Gas needed by each delayed message.
+///
+/// ### Returns
+/// The number of messages (rounded down), or 0 if `per_message_gas` is zero.
+pub fn delayed_messages_affordable(reservation_gas: u64, per_message_gas: u64) -> u64 {
+    reservation_gas
+        .checked_div(per_message_gas)
+        .unwrap_or(0)
+}
+
+/// Estimates the gas needed to cover the reply of a message (`REPLY_DEPOSIT_GAS`).
+///
+/// It is an approximation for replies with a small payload, if your `handle_reply` does heavy work
+/// you should deposit more gas.
+pub fn estimate_reply_gas() -> u64 {
+    REPLY_DEPOSIT_GAS
+}
+
+/// Estimates the current active era on the **testnet** based on the local block height.
+///
+/// # Returns
+/// The estimated active era number on testnet (relative to the reference era).
+///
+pub fn testnet_active_era_since_last_block_registered() -> u64 {
+    let eras_passed = eras_passed_since_init_block(LAST_TESTNET_ACTIVE_ERA_INIT_BLOCK);
+
+    LAST_TESTNET_ACTIVE_ERA_REGISTERED.saturating_add(eras_passed)
+}
+
+/// Estimates the current active era on the **mainnet** based on the local block height.
+/// 
+/// # Returns
+/// The estimated active era index on mainnet (relative to the reference era)
+///
+pub fn mainnet_active_era_since_last_timestamp_registered() -> u64 {
+    let eras_passed = eras_passed_since_init_block(LAST_MAINNET_ACTIVE_ERA_INIT_BLOCK);
+
+    LAST_MAINNET_ACTIVE_ERA_REGISTERED.saturating_add(eras_passed)
+}
+
+
+/// Estimates the current active era of the given network based on the local block height.
+///
+/// ### Parameters
+/// - `network`: Network whose reference era and block will be used.
+///
+/// ### Returns
+/// The estimated active era index (relative to the reference era).
+pub fn active_era_by_block(network: Network) -> u64 {
+    let eras_passed = eras_passed_since_init_block(network.last_active_era_init_block());
+
+    network.last_active_era_registered().saturating_add(eras_passed)
+}
+
+/// Estimates the current active era of the given network based on the block timestamp.
+///
+/// ### Parameters
+/// - `network`: Network whose reference era and timestamp will be used.
+///
+/// ### Returns
+/// The estimated active era index (relative to the reference era).
+pub fn active_era_by_timestamp(network: Network) -> u64 {
+    let eras_passed = eras_passed_since_init_timestamp(network.last_active_era_init_timestamp());
+
+    network.last_active_era_registered().saturating_add(eras_passed)
+}
+
+/// Estimates the current active era of the given network.
+///
+/// It uses the timestamp based estimation, since handling the time in milliseconds is more accurate
+/// than in blocks.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+pub fn active_era(network: Network) -> u64 {
+    active_era_by_timestamp(network)
+}
+
+/// Estimates the current active era of the given network reconciling the block and timestamp estimations.
+///
+/// ### Reconciliation
+/// - If both estimations are equal, or differ by one era (the block production lagged near an era
+///   boundary), the timestamp based era is used, since the time is the ground truth for eras.
+/// - If they differ by more than one era, the drift is too big to trust any of them, and the lower
+///   era is returned as the conservative value.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+pub fn reconciled_active_era(network: Network) -> u64 {
+    let era_by_block = active_era_by_block(network);
+    let era_by_timestamp = active_era_by_timestamp(network);
+
+    if era_by_block.abs_diff(era_by_timestamp) <= 1 {
+        era_by_timestamp
+    } else {
+        era_by_block.min(era_by_timestamp)
+    }
+}
+
+/// Estimates the era of the given network that will be active after a number of blocks.
+///
+/// Useful to know if the era will change before a delayed message is executed, for example.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `blocks`: Number of blocks in the future.
+///
+/// ### Returns
+/// The current era (based on block height) plus the era boundaries crossed in the given blocks.
+pub fn era_after_blocks(network: Network, blocks: u64) -> u64 {
+    let blocks_passed = blocks_passed_since_init_block(network.last_active_era_init_block())
+        .saturating_add(blocks);
+
+    network
+        .last_active_era_registered()
+        .saturating_add(blocks_passed / ONE_ERA_IN_BLOCKS)
+}
+
+/// Estimates the era of the given network that will be active after a number of milliseconds.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `ms`: Milliseconds in the future.
+///
+/// ### Returns
+/// The current era (based on timestamp) plus the era boundaries crossed in the given milliseconds.
+pub fn era_after_ms(network: Network, ms: u64) -> u64 {
+    era_after_ms_at(network, ms, exec::block_timestamp())
+}
+
+/// Same as `era_after_ms`, but the milliseconds are counted from the given timestamp instead of the
+/// current block timestamp.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `ms`: Milliseconds after `timestamp`.
+/// - `timestamp`: Timestamp in milliseconds.
+pub fn era_after_ms_at(network: Network, ms: u64, timestamp: u64) -> u64 {
+    let ms_passed = timestamp
+        .saturating_sub(network.last_active_era_init_timestamp())
+        .saturating_add(ms);
+
+    network
+        .last_active_era_registered()
+        .saturating_add(ms_passed / ONE_ERA_IN_MILLISECONDS)
+}
+
+/// Estimates the era of the given network in which a block was (or will be) produced.
+///
+/// Blocks before the reference block are extrapolated backward, saturating at era 0.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `block`: Block number.
+pub fn era_of_block(network: Network, block: u64) -> u64 {
+    let reference_era = network.last_active_era_registered();
+    let reference_block = network.last_active_era_init_block();
+
+    if block >= reference_block {
+        reference_era.saturating_add((block - reference_block) / ONE_ERA_IN_BLOCKS)
+    } else {
+        reference_era.saturating_sub((reference_block - block).div_ceil(ONE_ERA_IN_BLOCKS))
+    }
+}
+
+/// Checks if the era of the current block is different from the era of a stored block.
+///
+/// This is the core check for logic that runs once per era: store the block of the last run and
+/// run again when this function returns `true`.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `stored_block`: Block stored in the last run.
+pub fn era_changed_since(network: Network, stored_block: u64) -> bool {
+    let block_height = exec::block_height() as u64;
+
+    !same_era(network, block_height, stored_block)
+}
+
+/// Estimates the block at which an era of the given network starts.
+///
+/// Eras before the reference era are extrapolated backward, saturating at block 0.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `era`: Era index.
+pub fn era_start_block(network: Network, era: u64) -> u64 {
+    let reference_era = network.last_active_era_registered();
+    let reference_block = network.last_active_era_init_block();
+
+    if era >= reference_era {
+        reference_block.saturating_add((era - reference_era).saturating_mul(ONE_ERA_IN_BLOCKS))
+    } else {
+        reference_block.saturating_sub((reference_era - era).saturating_mul(ONE_ERA_IN_BLOCKS))
+    }
+}
+
+/// Estimates the block at the middle of an era of the given network.
+///
+/// Useful to take deterministic mid-era snapshots (like balances), far from both era boundaries.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `era`: Era index.
+pub fn era_midpoint_block(network: Network, era: u64) -> u64 {
+    era_start_block(network, era).saturating_add(ONE_ERA_IN_BLOCKS / 2)
+}
+
+/// Calculates how many blocks remain until an era of the given network starts.
+///
+/// The result can be used directly as the delay of a delayed message. If the era already started
+/// (including the current era), it returns 0.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `era`: Era index.
+pub fn blocks_until_era(network: Network, era: u64) -> u64 {
+    let block_height = exec::block_height() as u64;
+
+    era_start_block(network, era).saturating_sub(block_height)
+}
+
+/// Estimates the block at which an unbonding started at `start_block` is released.
+///
+/// The unbonding is completed at an era boundary, not exactly at `start_block + TOTAL_BLOCKS_TO_UNBOND`,
+/// so this function returns the first era start block at or after that raw completion block.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `start_block`: Block at which the unbonding started.
+pub fn unbond_end_era_aligned(network: Network, start_block: u64) -> u64 {
+    let raw_end_block = start_block.saturating_add(TOTAL_BLOCKS_TO_UNBOND);
+    let era = era_of_block(network, raw_end_block);
+    let era_start = era_start_block(network, era);
+
+    if era_start >= raw_end_block {
+        era_start
+    } else {
+        era_start_block(network, era.saturating_add(1))
+    }
+}
+
+/// Lists the era start blocks of the given network within a block range `[from, to)`.
+///
+/// Useful for indexers to bucket a range of scanned blocks by era. Empty ranges (`from >= to`)
+/// and ranges without an era boundary return an empty vec.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `from`: First block of the range (inclusive).
+/// - `to`: Last block of the range (exclusive).
+pub fn era_boundaries_in_range(network: Network, from: u64, to: u64) -> Vec<u64> {
+    let mut boundaries = vec![];
+
+    if from >= to {
+        return boundaries;
+    }
+
+    let era = era_of_block(network, from);
+    let mut boundary = era_start_block(network, era);
+
+    if boundary < from {
+        boundary = era_start_block(network, era.saturating_add(1));
+    }
+
+    while boundary >= from && boundary < to {
+        boundaries.push(boundary);
+        boundary = boundary.saturating_add(ONE_ERA_IN_BLOCKS);
+    }
+
+    boundaries
+}
+
+/// Calculates how many blocks of an unbonding window fall within an era of the given network.
+///
+/// The unbonding window is `[start_block, start_block + TOTAL_BLOCKS_TO_UNBOND)`, useful for per-era
+/// (pro-rata) accounting of unbonding stakes.
+///
+/// ### Parameters
+/// - `start_block`: Block at which the unbonding started.
+/// - `era`: Era index.
+/// - `network`: Network whose reference constants will be used.
+///
+/// ### Returns
+/// The number of blocks in both the unbonding window and the era (0 if they don't overlap).
+pub fn unbond_blocks_in_era(start_block: u64, era: u64, network: Network) -> u64 {
+    let unbond_end = start_block.saturating_add(TOTAL_BLOCKS_TO_UNBOND);
+    let era_start = era_start_block(network, era);
+    let era_end = era_start_block(network, era.saturating_add(1));
+
+    let overlap_start = start_block.max(era_start);
+    let overlap_end = unbond_end.min(era_end);
+
+    overlap_end.saturating_sub(overlap_start)
+}
+
+/// Calculates the block range covering the current era and the `n` past eras of the given network.
+///
+/// The range goes from the start block of the era `n` eras ago to the current block, useful to
+/// scan the events of the last eras. The start saturates at the reference block of the network.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `n`: Number of past eras (0 for the current era only).
+///
+/// ### Returns
+/// A tuple `(from_block, current_block)`.
+pub fn past_eras_block_range(network: Network, n: u64) -> (u64, u64) {
+    let block_height = exec::block_height() as u64;
+    let era = active_era_by_block(network).saturating_sub(n);
+    let from_block = era_start_block(network, era)
+        .max(network.last_active_era_init_block())
+        .min(block_height);
+
+    (from_block, block_height)
+}
+
+/// Checks if two blocks of the given network belong to the same era.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `a`: First block number.
+/// - `b`: Second block number.
+pub fn same_era(network: Network, a: u64, b: u64) -> bool {
+    era_of_block(network, a) == era_of_block(network, b)
+}
+
+/// Checks if a block of the given network is likely final, that is, its era is at least one era
+/// behind the era of the current block.
+///
+/// This is a conservative heuristic, not the consensus finality: blocks are usually finalized a few
+/// seconds after being produced, so a block from a past era is final in practice. Blocks of the current
+/// era (or future blocks) return `false` even if they were already finalized.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `block`: Block number.
+pub fn is_block_likely_final(network: Network, block: u64) -> bool {
+    let block_height = exec::block_height() as u64;
+
+    era_of_block(network, block) < era_of_block(network, block_height)
+}
+
+/// Estimates the era of the given network that was (or will be) active at a timestamp.
+///
+/// Useful to verify eras claimed with timestamps provided off-chain. Timestamps before the
+/// reference timestamp saturate to the reference era.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `timestamp`: Timestamp in milliseconds.
+pub fn era_at_timestamp(network: Network, timestamp: u64) -> u64 {
+    let ms_passed = timestamp.saturating_sub(network.last_active_era_init_timestamp());
+
+    network
+        .last_active_era_registered()
+        .saturating_add(ms_passed / ONE_ERA_IN_MILLISECONDS)
+}
+
+/// Estimates the era of the given network that will be active a number of milliseconds from now.
+///
+/// Alias of `era_after_ms`, named after `era_at_timestamp`, use `era_after_ms_at` to compute it from a
+/// given timestamp.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `ms_from_now`: Milliseconds in the future.
+pub fn era_at_future_ms(network: Network, ms_from_now: u64) -> u64 {
+    era_after_ms(network, ms_from_now)
+}
+
+/// Estimates the timestamp of the era boundary of the given network nearest to the current timestamp.
+///
+/// The boundaries are the start of the current era and the start of the next era, if the current
+/// timestamp is at the same distance of both, the start of the current era is returned. Timestamps
+/// before the reference timestamp saturate to the reference timestamp.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+pub fn nearest_era_boundary_ms(network: Network) -> u64 {
+    nearest_era_boundary_ms_at(network, exec::block_timestamp())
+}
+
+/// Same as `nearest_era_boundary_ms`, but the nearest boundary to the given timestamp (in milliseconds)
+/// instead of the current block timestamp.
+pub fn nearest_era_boundary_ms_at(network: Network, timestamp: u64) -> u64 {
+    let reference_timestamp = network.last_active_era_init_timestamp();
+    let ms_passed = timestamp.saturating_sub(reference_timestamp);
+    let ms_in_era = ms_passed % ONE_ERA_IN_MILLISECONDS;
+
+    let era_start = reference_timestamp.saturating_add(ms_passed - ms_in_era);
+
+    if ms_in_era <= ONE_ERA_IN_MILLISECONDS / 2 {
+        era_start
+    } else {
+        era_start.saturating_add(ONE_ERA_IN_MILLISECONDS)
+    }
+}
+
+/// Calculates the index of the current era within a rotation of `period` eras (`active_era % period`).
+///
+/// Useful for deterministic round-robin selection, for example rotating a responsibility each era.
+///
+/// ### Parameters
+/// - `period`: Number of eras of the rotation, if it is 0, the index is always 0.
+/// - `network`: Network whose reference constants will be used.
+pub fn era_rotation_index(period: u64, network: Network) -> u64 {
+    active_era(network)
+        .checked_rem(period)
+        .unwrap_or(0)
+}
+
+/// Estimates the current active era as a `(min_era, max_era)` range.
+///
+/// Block production can lag behind the wall clock, so the era computed from the block height
+/// and the era computed from the block timestamp may not match. This function returns both
+/// estimates ordered as bounds, so the contract can decide conservatively which one to use.
+///
+/// If both bounds are different, it means that the block time of the network has drifted from
+/// the expected ~3 seconds per block (clock/block drift) since the reference era.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+///
+/// ### Returns
+/// A tuple with the lowest and the highest estimated active era.
+pub fn active_era_bounds(network: Network) -> (u64, u64) {
+    active_era_bounds_at(network, exec::block_height() as u64, exec::block_timestamp())
+}
+
+/// Same as `active_era_bounds`, but with the given block height and timestamp instead of the current ones.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `block`: Block height to estimate the era by block.
+/// - `timestamp`: Timestamp in milliseconds to estimate the era by timestamp.
+pub fn active_era_bounds_at(network: Network, block: u64, timestamp: u64) -> (u64, u64) {
+    let blocks_passed = block.saturating_sub(network.last_active_era_init_block());
+    let era_by_block = network
+        .last_active_era_registered()
+        .saturating_add(blocks_passed / ONE_ERA_IN_BLOCKS);
+    let era_by_timestamp = era_at_timestamp(network, timestamp);
+
+    if era_by_block <= era_by_timestamp {
+        (era_by_block, era_by_timestamp)
+    } else {
+        (era_by_timestamp, era_by_block)
+    }
+}
+
+/// Calculates the oldest era whose staking rewards can still be claimed on the given network.
+///
+/// If the chain is younger than `HISTORY_DEPTH` eras, it returns the era 0.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+pub fn oldest_claimable_era(network: Network) -> u64 {
+    active_era(network).saturating_sub(HISTORY_DEPTH)
+}
+
+/// Returns the range of eras whose staking rewards can be claimed on the given network.
+///
+/// The range goes from `oldest_claimable_era` to the active era (both inclusive), so
+/// contracts can iterate the claimable eras directly.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+pub fn claimable_era_range(network: Network) -> RangeInclusive<u64> {
+    claimable_era_range_at(network, exec::block_timestamp())
+}
+
+/// Same as `claimable_era_range`, but the active era is estimated at the given timestamp (in milliseconds).
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `timestamp`: Timestamp in milliseconds.
+pub fn claimable_era_range_at(network: Network, timestamp: u64) -> RangeInclusive<u64> {
+    let active_era = era_at_timestamp(network, timestamp);
+
+    active_era.saturating_sub(HISTORY_DEPTH)..=active_era
+}
+
+/// Builds a key to store data per era in a map, with the format `"{prefix}:{era}"`.
+///
+/// The same prefix and era will always give the same key. To avoid collisions between different
+/// prefixes, the prefix should not contain the `:` character, if you cannot ensure it, use
+/// `era_storage_key_bytes` instead.
+///
+/// ### Parameters
+/// - `prefix`: Name of the data stored (for example, `"rewards"`).
+/// - `era`: Era of the data.
+pub fn era_storage_key(prefix: &str, era: u64) -> String {
+    format!("{prefix}:{era}")
+}
+
+/// Builds a byte key to store data per era in a map.
+///
+/// The key is the SCALE encoding of `(prefix, era)`, since the prefix is encoded with its length,
+/// different prefixes will never collide.
+///
+/// ### Parameters
+/// - `prefix`: Name of the data stored (for example, `"rewards"`).
+/// - `era`: Era of the data.
+pub fn era_storage_key_bytes(prefix: &str, era: u64) -> Vec<u8> {
+    (prefix, era).encode()
+}
+
+/// Errors returned by the era validation helpers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, TypeInfo)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub enum EraError {
+    /// The era is older than the oldest claimable era
+    EraExpired,
+    /// The era is after the active era
+    FutureEra,
+}
+
+/// Checks that an era is within the claimable window of the given network
+/// (`oldest_claimable_era..=active_era`).
+///
+/// ### Parameters
+/// - `era`: Era to claim.
+/// - `network`: Network whose reference constants will be used.
+///
+/// ### Errors
+/// - `EraError::EraExpired` if the era is older than the oldest claimable era.
+/// - `EraError::FutureEra` if the era is after the active era.
+pub fn ensure_era_claimable(era: u64, network: Network) -> Result<(), EraError> {
+    ensure_era_claimable_at(era, network, exec::block_timestamp())
+}
+
+/// Same as `ensure_era_claimable`, but the claimable window is estimated at the given timestamp
+/// (in milliseconds) instead of the current block timestamp.
+pub fn ensure_era_claimable_at(era: u64, network: Network, timestamp: u64) -> Result<(), EraError> {
+    let claimable_eras = claimable_era_range_at(network, timestamp);
+
+    if era < *claimable_eras.start() {
+        return Err(EraError::EraExpired);
+    }
+
+    if era > *claimable_eras.end() {
+        return Err(EraError::FutureEra);
+    }
+
+    Ok(())
+}
+
+/// Calculates how many blocks remain before an era falls out of the claimable window of the given network.
+///
+/// An era is claimable while it is within `claimable_era_range`, so it stops being claimable when the
+/// era `era + HISTORY_DEPTH + 1` starts. Useful for "claim before it expires" alerts.
+///
+/// ### Parameters
+/// - `era`: Era to claim.
+/// - `network`: Network whose reference constants will be used.
+///
+/// ### Returns
+/// The blocks remaining, or 0 if the era is no longer claimable.
+pub fn blocks_until_era_unclaimable(era: u64, network: Network) -> u64 {
+    let unclaimable_era = era
+        .saturating_add(HISTORY_DEPTH)
+        .saturating_add(1);
+
+    blocks_until_era(network, unclaimable_era)
+}
+
+/// Estimates the effective block time (in milliseconds) of the given network since its reference era.
+///
+/// It is `(block_timestamp - reference_timestamp) / (block_height - reference_block)`, if the result is
+/// above `BLOCK_TIME_MS`, the network is producing blocks slower than expected, and the block based
+/// estimations will drift from the timestamp based ones.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+///
+/// ### Returns
+/// The effective block time, or `BLOCK_TIME_MS` if no block has passed since the reference block.
+pub fn estimated_block_time_ms(network: Network) -> u64 {
+    let blocks_passed = blocks_passed_since_init_block(network.last_active_era_init_block());
+    let ms_passed = ms_passed_since_init_timestamp(network.last_active_era_init_timestamp());
+
+    ms_passed
+        .checked_div(blocks_passed)
+        .unwrap_or(BLOCK_TIME_MS)
+}
+
+/// Calculates the next era that a user can claim, given the last era claimed.
+///
+/// ### Parameters
+/// - `last_claimed_era`: Last era claimed by the user.
+/// - `network`: Network whose reference constants will be used.
+///
+/// ### Returns
+/// - `last_claimed_era + 1` if it is within `claimable_era_range`.
+/// - The oldest claimable era if the user is behind the claimable window.
+/// - `None` if the user already claimed up to the active era.
+pub fn next_claimable_era(last_claimed_era: u64, network: Network) -> Option<u64> {
+    next_claimable_era_at(last_claimed_era, network, exec::block_timestamp())
+}
+
+/// Same as `next_claimable_era`, but the claimable window is estimated at the given timestamp
+/// (in milliseconds) instead of the current block timestamp.
+pub fn next_claimable_era_at(last_claimed_era: u64, network: Network, timestamp: u64) -> Option<u64> {
+    let claimable_eras = claimable_era_range_at(network, timestamp);
+    let next_era = last_claimed_era.saturating_add(1);
+
+    if next_era > *claimable_eras.end() {
+        return None;
+    }
+
+    Some(next_era.max(*claimable_eras.start()))
+}
+
+/// Estimates the timestamp (in milliseconds) of any block of the given network.
 ///
-/// # Arguments
-/// - `f`: A closure returning `Result<T, E>`.
+/// The timestamp is extrapolated from the reference block and timestamp of the network using
+/// `BLOCK_TIME_MS`, so it is an approximation. Blocks before the reference block are
+/// extrapolated backward.
 ///
-/// # Panics
-/// Panics with the error if `f()` returns `Err`.
+/// This is the inverse of `block_at_timestamp`.
 ///
-/// # Example
-/// ```
-/// let result = panicking(|| Ok(42)); // returns 42
-/// let result = panicking(|| Err("failure")); // panics with "failure"
-/// ```
-pub fn panicking<T, E: Debug, F: FnOnce() -> Result<T, E>>(f: F) -> T {
-    match f() {
-        Ok(v) => v,
-        Err(e) => panic(e),
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `block`: Block number to translate.
+///
+/// ### Returns
+/// The estimated timestamp of the block.
+pub fn block_to_timestamp(network: Network, block: u64) -> u64 {
+    let reference_block = network.last_active_era_init_block();
+    let reference_timestamp = network.last_active_era_init_timestamp();
+
+    if block >= reference_block {
+        let ms_passed = (block - reference_block).saturating_mul(BLOCK_TIME_MS);
+        reference_timestamp.saturating_add(ms_passed)
+    } else {
+        let ms_before = (reference_block - block).saturating_mul(BLOCK_TIME_MS);
+        reference_timestamp.saturating_sub(ms_before)
     }
 }
 
-/// Terminates execution with a panic, logging the debug representation of the provided error.
+/// Estimates the block number of the given network at a timestamp (in milliseconds).
 ///
-/// This is a wrapper around `gstd::ext::panic` that formats the error for clarity.
-/// It is suitable for terminating execution inside smart contracts when a fatal error occurs.
-/// 
-/// This helps save on gas fees since the error is detected when calculating the total amount of gas used.
+/// The block is extrapolated from the reference block and timestamp of the network using
+/// `BLOCK_TIME_MS`, so it is an approximation. Timestamps before the reference timestamp are
+/// extrapolated backward.
 ///
-/// # Arguments
-/// - `err`: Any value implementing `Debug`, used for formatting the panic message.
+/// This is the inverse of `block_to_timestamp`.
 ///
-/// # Behavior
-/// Calls `ext::panic` with a `format!("{err:?}")` message and does **not** return.
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+/// - `timestamp`: Timestamp to translate.
 ///
-/// # Example
-/// ```
-/// panic("unrecoverable failure");
-/// panic(my_enum_error);
-/// ```
-pub fn panic(err: impl Debug) -> ! {
-    ext::panic(format!("{err:?}"))
+/// ### Returns
+/// The estimated block number at the timestamp.
+pub fn block_at_timestamp(network: Network, timestamp: u64) -> u64 {
+    let reference_block = network.last_active_era_init_block();
+    let reference_timestamp = network.last_active_era_init_timestamp();
+
+    if timestamp >= reference_timestamp {
+        let blocks_passed = (timestamp - reference_timestamp) / BLOCK_TIME_MS;
+        reference_block.saturating_add(blocks_passed)
+    } else {
+        let blocks_before = (reference_timestamp - timestamp) / BLOCK_TIME_MS;
+        reference_block.saturating_sub(blocks_before)
+    }
 }
 
-/// Estimates the current active era on the **testnet** based on the local block height.
+/// Calculates how many blocks have passed since the current era of the given network started.
 ///
-/// # Returns
-/// The estimated active era number on testnet (relative to the reference era).
+/// It is the complement of the blocks left for the next era, useful for phase based logic
+/// within an era (0 at the era start, up to `ONE_ERA_IN_BLOCKS - 1` at the end).
 ///
-pub fn testnet_active_era_since_last_block_registered() -> u64 {
-    let eras_passed = eras_passed_since_init_block(LAST_TESTNET_ACTIVE_ERA_INIT_BLOCK);
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+pub fn block_offset_in_era(network: Network) -> u64 {
+    blocks_passed_since_init_block(network.last_active_era_init_block()) % ONE_ERA_IN_BLOCKS
+}
 
-    LAST_TESTNET_ACTIVE_ERA_REGISTERED.saturating_add(eras_passed)
+/// Calculates the progress of the current era of the given network in permille (0 to 999).
+///
+/// It is based on the block timestamp, since it is more accurate than the block height.
+///
+/// ### Parameters
+/// - `network`: Network whose reference constants will be used.
+pub fn era_progress_permille(network: Network) -> u64 {
+    let ms_passed = ms_passed_since_init_timestamp(network.last_active_era_init_timestamp());
+
+    (ms_passed % ONE_ERA_IN_MILLISECONDS) * 1000 / ONE_ERA_IN_MILLISECONDS
 }
 
-/// Estimates the current active era on the **mainnet** based on the local block height.
-/// 
-/// # Returns
-/// The estimated active era index on mainnet (relative to the reference era)
+/// Calculates the part of a per-era emission that is not "earned" yet in the current era.
 ///
-pub fn mainnet_active_era_since_last_timestamp_registered() -> u64 {
-    let eras_passed = eras_passed_since_init_block(LAST_MAINNET_ACTIVE_ERA_INIT_BLOCK);
+/// It assumes that the emission is linear during the era, so the earned part is proportional
+/// to `era_progress_permille`.
+///
+/// ### Parameters
+/// - `total_era_emission`: Total amount emitted in one era.
+/// - `network`: Network whose reference constants will be used.
+pub fn era_emission_remaining(total_era_emission: u128, network: Network) -> u128 {
+    let progress = era_progress_permille(network) as u32;
 
-    LAST_MAINNET_ACTIVE_ERA_REGISTERED.saturating_add(eras_passed)
+    total_era_emission - mul_permille(total_era_emission, progress)
 }
 
+/// Estimates the block at which the current day started.
+///
+/// Days are counted from `genesis_timestamp` (use `0`, or any UTC midnight, to get the last UTC
+/// midnight), useful for daily reward epochs distinct from eras. The block is approximated going
+/// back from the current block the time passed since the day started, using `BLOCK_TIME_MS`, so
+/// it can be a few blocks off if the block time drifted.
+///
+/// ### Parameters
+/// - `genesis_timestamp`: Timestamp (in milliseconds) of the start of the first day.
+pub fn current_day_start_block(genesis_timestamp: u64) -> u64 {
+    let block_height = exec::block_height() as u64;
+    let ms_passed = ms_passed_since_init_timestamp(genesis_timestamp);
+    let ms_since_day_start = ms_passed % ONE_DAY_IN_MILLISECONDS;
+
+    block_height.saturating_sub(ms_since_day_start / BLOCK_TIME_MS)
+}
 
 /// Calculates how many **blocks remain** until the next era begins on the **testnet**.
 ///
@@ -189,6 +1503,73 @@ pub fn eras_passed_since_init_timestamp(era_init_timestamp: u64) -> u64 {
     eras_passed
 }
 
+/// Converts a number of eras to blocks (`eras * ONE_ERA_IN_BLOCKS`), saturating on overflow.
+///
+/// Useful with the staking constants, for example `eras_to_blocks(BONDING_DURATION_ERAS)`
+/// is equal to `TOTAL_BLOCKS_TO_UNBOND`.
+pub fn eras_to_blocks(eras: u64) -> u64 {
+    eras.saturating_mul(ONE_ERA_IN_BLOCKS)
+}
+
+/// Converts a number of eras to milliseconds (`eras * ONE_ERA_IN_MILLISECONDS`), saturating on overflow.
+///
+/// Useful with the staking constants, for example `eras_to_ms(BONDING_DURATION_ERAS)`
+/// is equal to `TOTAL_MILISECONDS_TO_UBOND`.
+pub fn eras_to_ms(eras: u64) -> u64 {
+    eras.saturating_mul(ONE_ERA_IN_MILLISECONDS)
+}
+
+/// Number of blocks during which the staking rewards of an era can be claimed (`HISTORY_DEPTH` eras).
+pub fn history_depth_in_blocks() -> u64 {
+    eras_to_blocks(HISTORY_DEPTH)
+}
+
+/// Number of blocks that a slash is deferred (`SLASH_DEFER_DURATION_ERAS` eras).
+pub fn slash_defer_duration_in_blocks() -> u64 {
+    eras_to_blocks(SLASH_DEFER_DURATION_ERAS)
+}
+
+/// Calculates how many full eras and leftover blocks have passed since a given block.
+///
+/// ### Parameters
+/// - `block`: The block number to use as reference.
+///
+/// ### Returns
+/// A tuple `(full_eras, remainder_blocks)`, where `full_eras * ONE_ERA_IN_BLOCKS + remainder_blocks`
+/// is equal to the blocks passed since the reference block.
+pub fn eras_and_blocks_since(block: u64) -> (u64, u64) {
+    let blocks_passed = blocks_passed_since_init_block(block);
+
+    (blocks_passed / ONE_ERA_IN_BLOCKS, blocks_passed % ONE_ERA_IN_BLOCKS)
+}
+
+/// Calculates how many full eras and leftover milliseconds have passed since a given timestamp.
+///
+/// ### Parameters
+/// - `timestamp`: The timestamp to use as reference.
+///
+/// ### Returns
+/// A tuple `(full_eras, remainder_ms)`, where `full_eras * ONE_ERA_IN_MILLISECONDS + remainder_ms`
+/// is equal to the milliseconds passed since the reference timestamp.
+pub fn eras_and_ms_since(timestamp: u64) -> (u64, u64) {
+    eras_and_ms_between(timestamp, exec::block_timestamp())
+}
+
+/// Calculates how many full eras and leftover milliseconds are between two timestamps.
+///
+/// ### Parameters
+/// - `from_timestamp`: Start timestamp.
+/// - `to_timestamp`: End timestamp, if it is before `from_timestamp` the result is `(0, 0)`.
+///
+/// ### Returns
+/// A tuple `(full_eras, remainder_ms)`, where `full_eras * ONE_ERA_IN_MILLISECONDS + remainder_ms`
+/// is equal to the milliseconds between both timestamps.
+pub fn eras_and_ms_between(from_timestamp: u64, to_timestamp: u64) -> (u64, u64) {
+    let ms_between = to_timestamp.saturating_sub(from_timestamp);
+
+    (ms_between / ONE_ERA_IN_MILLISECONDS, ms_between % ONE_ERA_IN_MILLISECONDS)
+}
+
 /// Calculates how many blocks have passed since a given initial block.
 ///
 /// ### Parameters
@@ -219,4 +1600,551 @@ pub fn ms_passed_since_init_timestamp(era_init_timestamp: u64) -> u64 {
         .unwrap_or(0);
 
     ms_passed
-}
\ No newline at end of file
+}
+
+/// Computes `a * b / c` rounded down, using a 256 bits intermediate product (saturates at `u128::MAX`).
+///
+/// `c` must not be zero.
+fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    const MASK: u128 = u64::MAX as u128;
+
+    let (a_lo, a_hi) = (a & MASK, a >> 64);
+    let (b_lo, b_hi) = (b & MASK, b >> 64);
+
+    let lo_lo = a_lo * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_lo = a_hi * b_lo;
+    let hi_hi = a_hi * b_hi;
+
+    let mid = (lo_lo >> 64) + (lo_hi & MASK) + (hi_lo & MASK);
+    let lo = (lo_lo & MASK) | (mid << 64);
+    let hi = hi_hi + (lo_hi >> 64) + (hi_lo >> 64) + (mid >> 64);
+
+    if hi >= c {
+        return u128::MAX;
+    }
+
+    // Long division of the 256 bits product (hi, lo) by c
+    let mut remainder = hi;
+    let mut quotient = 0u128;
+
+    for i in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((lo >> i) & 1);
+        quotient <<= 1;
+
+        if carry == 1 || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+
+    quotient
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stake_share_matches_naive_math_for_small_values() {
+        let cases = [(1, 3, 10), (250, 1_000, 4_000), (7, 9, u64::MAX as u128), (0, 5, 100)];
+
+        for (stake, total_stake, pool) in cases {
+            assert_eq!(stake_share(stake, total_stake, pool), pool * stake / total_stake);
+        }
+    }
+
+    #[test]
+    fn stake_share_does_not_overflow_with_large_values() {
+        let pool = 10u128.pow(30);
+        let stake = 10u128.pow(20);
+        let total_stake = 10u128.pow(25);
+
+        assert!(pool.checked_mul(stake).is_none());
+        assert_eq!(stake_share(stake, total_stake, pool), 10u128.pow(25));
+        assert_eq!(stake_share(u128::MAX / 2, u128::MAX, u128::MAX), u128::MAX / 2);
+        assert_eq!(stake_share(u128::MAX, u128::MAX, u128::MAX), u128::MAX);
+    }
+
+    #[test]
+    fn stake_share_edge_cases() {
+        assert_eq!(stake_share(10, 0, 1_000), 0);
+        assert_eq!(stake_share(2, 1, u128::MAX), u128::MAX);
+    }
+
+    #[test]
+    fn mul_div_rounds_down_the_256_bits_product() {
+        assert_eq!(mul_div(u128::MAX, u128::MAX - 1, u128::MAX), u128::MAX - 1);
+        assert_eq!(mul_div(u128::MAX - 1, u128::MAX - 1, u128::MAX), u128::MAX - 2);
+        assert_eq!(mul_div(1 << 127, 4, 8), 1 << 126);
+        assert_eq!(mul_div(u128::MAX, 3, 7), u128::MAX / 7 * 3 + (u128::MAX % 7) * 3 / 7);
+    }
+
+    #[test]
+    fn apply_fee_splits_net_and_fee() {
+        assert_eq!(apply_fee(1_000, 0), (1_000, 0));
+        assert_eq!(apply_fee(1_000, 25), (975, 25));
+        assert_eq!(apply_fee(1_000, 1_000), (0, 1_000));
+        assert_eq!(apply_fee(1_000, 1_500), (0, 1_000));
+    }
+
+    #[test]
+    fn apply_fee_rounds_the_fee_down() {
+        assert_eq!(apply_fee(999, 1), (999, 0));
+        assert_eq!(apply_fee(1_999, 1), (1_998, 1));
+        assert_eq!(apply_fee(u128::MAX, 10), (u128::MAX - u128::MAX / 100, u128::MAX / 100));
+    }
+
+    #[test]
+    fn convert_units_scales_between_decimals() {
+        assert_eq!(convert_units(5, 6, 12), Some(5_000_000));
+        assert_eq!(convert_units(5_000_000, 12, 6), Some(5));
+        assert_eq!(convert_units(42, 8, 8), Some(42));
+    }
+
+    #[test]
+    fn convert_units_rounds_down_and_detects_overflow() {
+        assert_eq!(convert_units(1_999_999, 12, 6), Some(1));
+        assert_eq!(convert_units(u128::MAX, 0, 1), None);
+        assert_eq!(convert_units(1, 0, 40), None);
+        assert_eq!(convert_units(u128::MAX, 40, 0), Some(0));
+    }
+
+    #[test]
+    fn project_rewards_compounds_per_era() {
+        assert_eq!(project_rewards(1_000, 100, 0), 1_000);
+        assert_eq!(project_rewards(1_000, 100, 1), 1_100);
+        assert_eq!(project_rewards(1_000, 100, 3), 1_331);
+        assert_eq!(project_rewards(1_000_000, 1, 2), 1_002_001);
+    }
+
+    #[test]
+    fn project_rewards_stops_when_the_reward_rounds_to_zero_or_saturates() {
+        assert_eq!(project_rewards(9, 100, 1_000), 9);
+        assert_eq!(project_rewards(1_000, 0, 10), 1_000);
+        assert_eq!(project_rewards(u128::MAX / 2, 1_000, 5), u128::MAX);
+    }
+
+    #[test]
+    fn project_rewards_partial_adds_a_fraction_of_the_next_era() {
+        assert_eq!(project_rewards_partial(1_000, 100, 1, 0), 1_100);
+        assert_eq!(project_rewards_partial(1_000, 100, 1, 500), 1_155);
+        assert_eq!(project_rewards_partial(1_000, 100, 1, 1_000), project_rewards(1_000, 100, 2));
+        assert_eq!(project_rewards_partial(1_000, 100, 0, 2_000), 1_100);
+    }
+
+    #[test]
+    fn decayed_emission_matches_the_geometric_reference() {
+        let base = 1_000_000u128;
+        let mut reference = base;
+
+        for eras in 0..5 {
+            assert_eq!(decayed_emission(base, 10, eras), reference);
+            reference -= reference * 10 / 1000;
+        }
+
+        assert_eq!(decayed_emission(base, 10, 3), 970_299);
+    }
+
+    #[test]
+    fn decayed_emission_edge_cases() {
+        assert_eq!(decayed_emission(1_000, 0, 100), 1_000);
+        assert_eq!(decayed_emission(1_000, 1_000, 1), 0);
+        assert_eq!(decayed_emission(1_000, 5_000, 1), 0);
+        assert_eq!(decayed_emission(99, 10, u64::MAX), 99);
+    }
+
+    #[test]
+    fn era_of_block_and_era_start_block_are_consistent() {
+        for network in [Network::Testnet, Network::Mainnet] {
+            let reference_era = network.last_active_era_registered();
+            let reference_block = network.last_active_era_init_block();
+
+            assert_eq!(era_start_block(network, reference_era), reference_block);
+            assert_eq!(era_of_block(network, reference_block), reference_era);
+            assert_eq!(era_of_block(network, reference_block - 1), reference_era - 1);
+            assert_eq!(era_of_block(network, reference_block + ONE_ERA_IN_BLOCKS - 1), reference_era);
+            assert_eq!(era_of_block(network, reference_block + ONE_ERA_IN_BLOCKS), reference_era + 1);
+
+            for era in [reference_era - 10, reference_era + 10] {
+                assert_eq!(era_of_block(network, era_start_block(network, era)), era);
+            }
+        }
+    }
+
+    #[test]
+    fn era_start_block_saturates_before_the_first_block() {
+        assert_eq!(era_start_block(Network::Testnet, 0), 0);
+        assert_eq!(era_start_block(Network::Testnet, 1), 0);
+    }
+
+    #[test]
+    fn same_era_compares_the_era_of_both_blocks() {
+        let network = Network::Mainnet;
+        let era_start = era_start_block(network, network.last_active_era_registered());
+
+        assert!(same_era(network, era_start, era_start + ONE_ERA_IN_BLOCKS - 1));
+        assert!(!same_era(network, era_start - 1, era_start));
+        assert!(!same_era(network, era_start, era_start + ONE_ERA_IN_BLOCKS));
+    }
+
+    #[test]
+    fn unbond_end_era_aligned_rounds_up_to_the_next_era_start() {
+        let network = Network::Mainnet;
+        let era_start = era_start_block(network, network.last_active_era_registered());
+
+        assert_eq!(unbond_end_era_aligned(network, era_start), era_start + TOTAL_BLOCKS_TO_UNBOND);
+        assert_eq!(
+            unbond_end_era_aligned(network, era_start + 1),
+            era_start + TOTAL_BLOCKS_TO_UNBOND + ONE_ERA_IN_BLOCKS
+        );
+        assert_eq!(
+            unbond_end_era_aligned(network, era_start + ONE_ERA_IN_BLOCKS - 1),
+            era_start + TOTAL_BLOCKS_TO_UNBOND + ONE_ERA_IN_BLOCKS
+        );
+    }
+
+    #[test]
+    fn duration_human_formats_days_hours_and_minutes() {
+        assert_eq!(era_duration_human(), "12h 0m");
+        assert_eq!(unbond_duration_human(), "7d 0h 0m");
+        assert_eq!(duration_human(0), "0h 0m");
+        assert_eq!(duration_human(59_999), "0h 0m");
+        assert_eq!(duration_human(90_061_000), "1d 1h 1m");
+    }
+
+    #[test]
+    fn framed_reply_round_trips() {
+        let reply = build_framed_reply("Vft", "Transfer", &(true, 42u128));
+
+        assert_eq!(reply, ("Vft", "Transfer", (true, 42u128)).encode());
+        assert_eq!(decode_sails_reply::<(bool, u128)>(&reply).unwrap(), (true, 42u128));
+    }
+
+    #[test]
+    fn decode_sails_reply_fails_with_another_type() {
+        let reply = build_framed_reply("Vft", "Transfer", &true);
+
+        assert!(decode_sails_reply::<u128>(&reply).is_err());
+        assert!(decode_sails_reply::<bool>(&[]).is_err());
+    }
+
+    #[test]
+    fn check_and_consume_nonce_rejects_replays() {
+        let mut next_nonce = 0;
+
+        assert!(check_and_consume_nonce(0, &mut next_nonce));
+        assert_eq!(next_nonce, 1);
+        assert!(!check_and_consume_nonce(0, &mut next_nonce));
+        assert!(!check_and_consume_nonce(2, &mut next_nonce));
+        assert_eq!(next_nonce, 1);
+        assert!(check_and_consume_nonce(1, &mut next_nonce));
+
+        let mut next_nonce = u64::MAX;
+
+        assert!(check_and_consume_nonce(u64::MAX, &mut next_nonce));
+        assert_eq!(next_nonce, 0);
+    }
+
+    #[test]
+    fn read_schema_version_splits_the_first_byte() {
+        let bytes = [2u8.encode(), 7u32.encode()].concat();
+        let (version, remaining) = read_schema_version(&bytes).unwrap();
+
+        assert_eq!(version, 2);
+        assert_eq!(remaining, 7u32.encode());
+        assert_eq!(read_schema_version(&[]), None);
+    }
+
+    #[test]
+    fn extract_trace_id_splits_the_first_16_bytes() {
+        let id = [9u8; 16];
+        let bytes = [id.encode(), String::from("payload").encode()].concat();
+        let (trace_id, remaining) = extract_trace_id(&bytes).unwrap();
+
+        assert_eq!(trace_id, id);
+        assert_eq!(remaining, String::from("payload").encode());
+        assert_eq!(extract_trace_id(&[0u8; 15]), None);
+    }
+
+    #[test]
+    fn estimate_create_program_gas_is_linear_in_code_and_payload() {
+        assert_eq!(estimate_create_program_gas(0, 0), CREATE_PROGRAM_BASE_GAS);
+        assert_eq!(
+            estimate_create_program_gas(1_000, 10),
+            CREATE_PROGRAM_BASE_GAS + 1_000 * CREATE_PROGRAM_GAS_PER_CODE_BYTE + 10 * CREATE_PROGRAM_GAS_PER_PAYLOAD_BYTE
+        );
+        assert_eq!(estimate_create_program_gas(usize::MAX, 0), u64::MAX);
+    }
+
+    #[test]
+    fn block_to_timestamp_and_block_at_timestamp_are_inverse() {
+        for network in [Network::Testnet, Network::Mainnet] {
+            let reference_block = network.last_active_era_init_block();
+            let reference_timestamp = network.last_active_era_init_timestamp();
+
+            assert_eq!(block_to_timestamp(network, reference_block), reference_timestamp);
+            assert_eq!(block_to_timestamp(network, reference_block + 10), reference_timestamp + 10 * BLOCK_TIME_MS);
+            assert_eq!(block_to_timestamp(network, reference_block - 10), reference_timestamp - 10 * BLOCK_TIME_MS);
+
+            for block in [reference_block - 1_000, reference_block, reference_block + 1_000] {
+                assert_eq!(block_at_timestamp(network, block_to_timestamp(network, block)), block);
+            }
+
+            assert_eq!(block_at_timestamp(network, reference_timestamp + BLOCK_TIME_MS - 1), reference_block);
+        }
+    }
+
+    #[test]
+    fn era_storage_keys_are_distinct_per_prefix_and_era() {
+        assert_eq!(era_storage_key("rewards", 7), "rewards:7");
+        assert_ne!(era_storage_key("rewards", 7), era_storage_key("rewards", 8));
+        assert_ne!(era_storage_key("rewards", 7), era_storage_key("stakes", 7));
+
+        assert_eq!(era_storage_key_bytes("rewards", 7), era_storage_key_bytes("rewards", 7));
+        assert_ne!(era_storage_key_bytes("rewards", 7), era_storage_key_bytes("rewards", 8));
+        assert_ne!(era_storage_key_bytes("a:1", 2), era_storage_key_bytes("a", 12));
+    }
+
+    #[test]
+    fn check_guard_only_accepts_the_current_nonce() {
+        assert!(check_guard(3, 3));
+        assert!(!check_guard(2, 3));
+    }
+
+    #[test]
+    fn era_at_timestamp_crosses_era_boundaries() {
+        let network = Network::Mainnet;
+        let reference_era = network.last_active_era_registered();
+        let reference_timestamp = network.last_active_era_init_timestamp();
+
+        assert_eq!(era_at_timestamp(network, reference_timestamp), reference_era);
+        assert_eq!(era_at_timestamp(network, reference_timestamp + ONE_ERA_IN_MILLISECONDS - 1), reference_era);
+        assert_eq!(era_at_timestamp(network, reference_timestamp + ONE_ERA_IN_MILLISECONDS), reference_era + 1);
+        assert_eq!(era_at_timestamp(network, reference_timestamp - 1), reference_era);
+    }
+
+    #[test]
+    fn eras_and_ms_between_returns_full_eras_and_remainder() {
+        assert_eq!(eras_and_ms_between(1_000, 1_000), (0, 0));
+        assert_eq!(eras_and_ms_between(0, 2 * ONE_ERA_IN_MILLISECONDS + 5), (2, 5));
+        assert_eq!(eras_and_ms_between(5, 0), (0, 0));
+    }
+
+    #[test]
+    fn per_era_reward_from_apy_splits_the_annual_reward() {
+        let principal = 730 * ONE_TOKEN;
+
+        assert_eq!(per_era_reward_from_apy(principal, 100), ONE_TOKEN / 10);
+        assert_eq!(per_era_reward_from_apy(principal, 0), 0);
+        assert_eq!(per_era_reward_from_apy(100, 100), 0);
+    }
+
+    #[test]
+    fn decode_relay_sender_splits_the_original_sender() {
+        let sender = ActorId::from([4u8; 32]);
+        let bytes = [sender.encode(), 10u64.encode()].concat();
+        let (original_sender, remaining) = decode_relay_sender(&bytes).unwrap();
+
+        assert_eq!(original_sender, sender);
+        assert_eq!(remaining, 10u64.encode());
+        assert!(decode_relay_sender(&[0u8; 31]).is_err());
+    }
+
+    #[test]
+    fn era_boundaries_in_range_lists_the_era_starts() {
+        let network = Network::Mainnet;
+        let era_start = era_start_block(network, network.last_active_era_registered());
+
+        assert_eq!(era_boundaries_in_range(network, era_start, era_start), vec![]);
+        assert_eq!(era_boundaries_in_range(network, era_start + 1, era_start + 100), vec![]);
+        assert_eq!(
+            era_boundaries_in_range(network, era_start, era_start + 2 * ONE_ERA_IN_BLOCKS + 1),
+            vec![era_start, era_start + ONE_ERA_IN_BLOCKS, era_start + 2 * ONE_ERA_IN_BLOCKS]
+        );
+        assert_eq!(
+            era_boundaries_in_range(network, era_start - 1, era_start + ONE_ERA_IN_BLOCKS),
+            vec![era_start]
+        );
+    }
+
+    #[test]
+    fn slash_amount_never_exceeds_the_stake() {
+        assert_eq!(slash_amount(1_000, 10), 10);
+        assert_eq!(slash_amount(1_000, 0), 0);
+        assert_eq!(slash_amount(1_000, 2_000), 1_000);
+        assert_eq!(slash_applies_at_era(100), 100 + SLASH_DEFER_DURATION_ERAS);
+        assert_eq!(slash_applies_at_era(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn unbond_blocks_in_era_counts_the_overlap() {
+        let network = Network::Mainnet;
+        let era = network.last_active_era_registered();
+        let era_start = era_start_block(network, era);
+        let start_block = era_start + 100;
+
+        assert_eq!(unbond_blocks_in_era(start_block, era - 1, network), 0);
+        assert_eq!(unbond_blocks_in_era(start_block, era, network), ONE_ERA_IN_BLOCKS - 100);
+        assert_eq!(unbond_blocks_in_era(start_block, era + 1, network), ONE_ERA_IN_BLOCKS);
+        assert_eq!(unbond_blocks_in_era(start_block, era + 14, network), 100);
+        assert_eq!(unbond_blocks_in_era(start_block, era + 15, network), 0);
+
+        let total: u64 = (era..=era + 14)
+            .map(|era| unbond_blocks_in_era(start_block, era, network))
+            .sum();
+
+        assert_eq!(total, TOTAL_BLOCKS_TO_UNBOND);
+    }
+
+    #[test]
+    fn estimate_message_gas_is_linear_in_the_payload() {
+        assert_eq!(estimate_message_gas(0), MESSAGE_BASE_GAS);
+        assert_eq!(estimate_message_gas(100), MESSAGE_BASE_GAS + 100 * MESSAGE_GAS_PER_PAYLOAD_BYTE);
+        assert_eq!(estimate_message_gas(usize::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn apr_permille_from_per_era_is_the_inverse_of_per_era_reward() {
+        let principal = 730 * ONE_TOKEN;
+        let per_era_reward = per_era_reward_from_apy(principal, 100);
+
+        assert_eq!(apr_permille_from_per_era(principal, per_era_reward), 100);
+        assert_eq!(apr_permille_from_per_era(0, per_era_reward), 0);
+        assert_eq!(apr_permille_from_per_era(1, u128::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn delayed_messages_affordable_divides_the_reservation() {
+        assert_eq!(delayed_messages_affordable(10_000, 3_000), 3);
+        assert_eq!(delayed_messages_affordable(2_999, 3_000), 0);
+        assert_eq!(delayed_messages_affordable(10_000, 0), 0);
+    }
+
+    #[test]
+    fn era_midpoint_block_is_halfway_between_era_starts() {
+        let network = Network::Mainnet;
+        let era = network.last_active_era_registered();
+        let era_start = era_start_block(network, era);
+        let next_era_start = era_start_block(network, era + 1);
+
+        assert_eq!(era_midpoint_block(network, era), (era_start + next_era_start) / 2);
+        assert_eq!(era_of_block(network, era_midpoint_block(network, era)), era);
+    }
+
+    #[test]
+    fn active_era_bounds_orders_block_and_timestamp_eras() {
+        let network = Network::Testnet;
+        let era = network.last_active_era_registered();
+        let block = network.last_active_era_init_block() + 5 * ONE_ERA_IN_BLOCKS;
+        let timestamp = network.last_active_era_init_timestamp() + 4 * ONE_ERA_IN_MILLISECONDS;
+
+        assert_eq!(active_era_bounds_at(network, block, timestamp), (era + 4, era + 5));
+        assert_eq!(active_era_bounds_at(network, block - ONE_ERA_IN_BLOCKS, timestamp + ONE_ERA_IN_MILLISECONDS), (era + 4, era + 5));
+        assert_eq!(active_era_bounds_at(network, block, timestamp + ONE_ERA_IN_MILLISECONDS), (era + 5, era + 5));
+    }
+
+    #[test]
+    fn next_unbond_completion_skips_completed_unbondings() {
+        let starts = [100, 50, 300];
+
+        assert_eq!(next_unbond_completion_at(&starts, 0), Some(50 + TOTAL_BLOCKS_TO_UNBOND));
+        assert_eq!(next_unbond_completion_at(&starts, 50 + TOTAL_BLOCKS_TO_UNBOND), Some(100 + TOTAL_BLOCKS_TO_UNBOND));
+        assert_eq!(next_unbond_completion_at(&starts, 300 + TOTAL_BLOCKS_TO_UNBOND), None);
+        assert_eq!(next_unbond_completion_at(&[], 0), None);
+    }
+
+    #[test]
+    fn rate_limiter_allows_once_per_era() {
+        let network = Network::Mainnet;
+        let era = network.last_active_era_registered();
+        let timestamp = network.last_active_era_init_timestamp();
+
+        assert_eq!(RateLimiter::allow_at(era - 1, network, timestamp), Some(era));
+        assert_eq!(RateLimiter::allow_at(era, network, timestamp), None);
+        assert_eq!(RateLimiter::allow_at(era, network, timestamp + ONE_ERA_IN_MILLISECONDS - 1), None);
+        assert_eq!(RateLimiter::allow_at(era, network, timestamp + ONE_ERA_IN_MILLISECONDS), Some(era + 1));
+    }
+
+    #[test]
+    fn ensure_era_claimable_checks_the_window() {
+        let network = Network::Mainnet;
+        let era = network.last_active_era_registered();
+        let timestamp = network.last_active_era_init_timestamp();
+
+        assert_eq!(ensure_era_claimable_at(era, network, timestamp), Ok(()));
+        assert_eq!(ensure_era_claimable_at(era - HISTORY_DEPTH, network, timestamp), Ok(()));
+        assert_eq!(ensure_era_claimable_at(era - HISTORY_DEPTH - 1, network, timestamp), Err(EraError::EraExpired));
+        assert_eq!(ensure_era_claimable_at(era + 1, network, timestamp), Err(EraError::FutureEra));
+        assert_eq!(ensure_era_claimable_at(era + 1, network, timestamp + ONE_ERA_IN_MILLISECONDS), Ok(()));
+    }
+
+    #[test]
+    fn era_conversions_match_the_staking_constants() {
+        assert_eq!(eras_to_blocks(BONDING_DURATION_ERAS), TOTAL_BLOCKS_TO_UNBOND);
+        assert_eq!(eras_to_ms(BONDING_DURATION_ERAS), TOTAL_MILISECONDS_TO_UBOND);
+        assert_eq!(eras_to_ms(1), eras_to_blocks(1) * BLOCK_TIME_MS);
+        assert_eq!(history_depth_in_blocks(), HISTORY_DEPTH * ONE_ERA_IN_BLOCKS);
+        assert_eq!(slash_defer_duration_in_blocks(), SLASH_DEFER_DURATION_ERAS * ONE_ERA_IN_BLOCKS);
+        assert_eq!(eras_to_blocks(u64::MAX), u64::MAX);
+        assert_eq!(eras_to_ms(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn eras_per_year_covers_one_year() {
+        let year_in_eras_ms = ERAS_PER_YEAR * ONE_ERA_IN_MILLISECONDS;
+
+        assert!(year_in_eras_ms <= ONE_YEAR_IN_MILLISECONDS);
+        assert!(ONE_YEAR_IN_MILLISECONDS - year_in_eras_ms < ONE_ERA_IN_MILLISECONDS);
+        assert_eq!(BLOCKS_PER_YEAR * BLOCK_TIME_MS, year_in_eras_ms);
+    }
+
+    #[test]
+    fn apr_permille_keeps_precision_when_scaling_overflows() {
+        let k = 10u128.pow(33);
+        let per_era_reward = 3 * k;
+        let principal = 2 * ERAS_PER_YEAR as u128 * k;
+
+        assert!((per_era_reward * ERAS_PER_YEAR as u128).checked_mul(1000).is_none());
+        assert_eq!(apr_permille_from_per_era(principal, per_era_reward), 1500);
+    }
+
+    #[test]
+    fn next_claimable_era_within_the_window() {
+        let network = Network::Mainnet;
+        let era = network.last_active_era_registered();
+        let timestamp = network.last_active_era_init_timestamp();
+
+        assert_eq!(next_claimable_era_at(era - 5, network, timestamp), Some(era - 4));
+        assert_eq!(next_claimable_era_at(0, network, timestamp), Some(era - HISTORY_DEPTH));
+        assert_eq!(next_claimable_era_at(era, network, timestamp), None);
+        assert_eq!(next_claimable_era_at(era, network, timestamp + ONE_ERA_IN_MILLISECONDS), Some(era + 1));
+    }
+
+    #[test]
+    fn era_after_ms_counts_from_the_timestamp() {
+        let network = Network::Mainnet;
+        let era = network.last_active_era_registered();
+        let timestamp = network.last_active_era_init_timestamp() + ONE_ERA_IN_MILLISECONDS / 2;
+
+        assert_eq!(era_after_ms_at(network, 0, timestamp), era);
+        assert_eq!(era_after_ms_at(network, ONE_ERA_IN_MILLISECONDS / 2 - 1, timestamp), era);
+        assert_eq!(era_after_ms_at(network, ONE_ERA_IN_MILLISECONDS / 2, timestamp), era + 1);
+        assert_eq!(era_after_ms_at(network, ONE_DAY_IN_MILLISECONDS, timestamp), era + 2);
+        assert_eq!(era_after_ms_at(network, ONE_DAY_IN_MILLISECONDS, timestamp), era_at_timestamp(network, timestamp + ONE_DAY_IN_MILLISECONDS));
+    }
+
+    #[test]
+    fn nearest_era_boundary_rounds_to_the_closest_start() {
+        let network = Network::Mainnet;
+        let era_start = network.last_active_era_init_timestamp() + ONE_ERA_IN_MILLISECONDS;
+        let half_era = ONE_ERA_IN_MILLISECONDS / 2;
+
+        assert_eq!(nearest_era_boundary_ms_at(network, era_start), era_start);
+        assert_eq!(nearest_era_boundary_ms_at(network, era_start + half_era), era_start);
+        assert_eq!(nearest_era_boundary_ms_at(network, era_start + half_era + 1), era_start + ONE_ERA_IN_MILLISECONDS);
+        assert_eq!(nearest_era_boundary_ms_at(network, era_start - 1), era_start);
+        assert_eq!(nearest_era_boundary_ms_at(network, 0), network.last_active_era_init_timestamp());
+    }
+}
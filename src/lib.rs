@@ -1,6 +1,7 @@
 #![no_std]
 #![allow(clippy::crate_in_macro_def)]
 mod macros;
-mod builders;
+pub mod builders;
 pub mod consts;
-pub mod funcs;
\ No newline at end of file
+pub mod funcs;
+pub mod network;
\ No newline at end of file
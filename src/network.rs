@@ -0,0 +1,124 @@
+use gstd::exec;
+use crate::consts::*;
+
+/// Timestamp (2023-01-01) used to check that a reference timestamp is plausible.
+const MIN_PLAUSIBLE_TIMESTAMP: u64 = 1_672_531_200_000;
+
+/// Vara network to use when estimating eras.
+///
+/// Each network has its own reference era, block and timestamp (see `consts`), so the
+/// era helpers that receive a `Network` will use the reference values of the selected one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Testnet,
+    Mainnet,
+}
+
+/// Reference values used by the era helpers for a network.
+///
+/// Each `Network` has its own config (see `Network::config`), but you can build your own config to
+/// validate custom reference values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetworkConfig {
+    pub last_active_era_registered: u64,
+    pub last_active_era_init_block: u64,
+    pub last_active_era_init_timestamp: u64,
+    pub one_era_in_blocks: u64,
+    pub one_era_in_milliseconds: u64,
+    pub block_time_ms: u64,
+}
+
+impl NetworkConfig {
+    /// Sanity checks the reference values of the config.
+    ///
+    /// ### Errors
+    /// - If the reference block or era duration is zero.
+    /// - If `one_era_in_blocks * block_time_ms` is not equal to `one_era_in_milliseconds`.
+    /// - If the reference timestamp is not plausible (before 2023 or after the current block timestamp).
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.last_active_era_init_block == 0 {
+            return Err("Reference era init block cant be zero");
+        }
+
+        if self.one_era_in_blocks == 0 || self.one_era_in_milliseconds == 0 {
+            return Err("Era duration cant be zero");
+        }
+
+        if self.one_era_in_blocks.saturating_mul(self.block_time_ms) != self.one_era_in_milliseconds {
+            return Err("Era duration in blocks does not match the era duration in milliseconds");
+        }
+
+        if self.last_active_era_init_timestamp < MIN_PLAUSIBLE_TIMESTAMP {
+            return Err("Reference era init timestamp is too old");
+        }
+
+        if self.last_active_era_init_timestamp > exec::block_timestamp() {
+            return Err("Reference era init timestamp is in the future");
+        }
+
+        Ok(())
+    }
+}
+
+impl Network {
+    /// Reference values of the network.
+    pub const fn config(self) -> NetworkConfig {
+        NetworkConfig {
+            last_active_era_registered: self.last_active_era_registered(),
+            last_active_era_init_block: self.last_active_era_init_block(),
+            last_active_era_init_timestamp: self.last_active_era_init_timestamp(),
+            one_era_in_blocks: ONE_ERA_IN_BLOCKS,
+            one_era_in_milliseconds: ONE_ERA_IN_MILLISECONDS,
+            block_time_ms: BLOCK_TIME_MS,
+        }
+    }
+
+    /// Last active era registered on the network used as a reference point.
+    pub const fn last_active_era_registered(self) -> u64 {
+        match self {
+            Network::Testnet => LAST_TESTNET_ACTIVE_ERA_REGISTERED,
+            Network::Mainnet => LAST_MAINNET_ACTIVE_ERA_REGISTERED,
+        }
+    }
+
+    /// Block number at which the reference era started on the network.
+    pub const fn last_active_era_init_block(self) -> u64 {
+        match self {
+            Network::Testnet => LAST_TESTNET_ACTIVE_ERA_INIT_BLOCK,
+            Network::Mainnet => LAST_MAINNET_ACTIVE_ERA_INIT_BLOCK,
+        }
+    }
+
+    /// Timestamp at which the reference era started on the network.
+    pub const fn last_active_era_init_timestamp(self) -> u64 {
+        match self {
+            Network::Testnet => LAST_TESTNET_ACTIVE_ERA_INIT_TIMESTAMP,
+            Network::Mainnet => LAST_MAINNET_ACTIVE_ERA_INIT_TIMESTAMP,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_rejects_broken_constants() {
+        let config = Network::Mainnet.config();
+
+        let zero_block = NetworkConfig { last_active_era_init_block: 0, ..config };
+        assert_eq!(zero_block.validate(), Err("Reference era init block cant be zero"));
+
+        let zero_blocks = NetworkConfig { one_era_in_blocks: 0, ..config };
+        assert_eq!(zero_blocks.validate(), Err("Era duration cant be zero"));
+
+        let zero_ms = NetworkConfig { one_era_in_milliseconds: 0, ..config };
+        assert_eq!(zero_ms.validate(), Err("Era duration cant be zero"));
+
+        let mismatch = NetworkConfig { block_time_ms: config.block_time_ms + 1, ..config };
+        assert_eq!(mismatch.validate(), Err("Era duration in blocks does not match the era duration in milliseconds"));
+
+        let too_old = NetworkConfig { last_active_era_init_timestamp: MIN_PLAUSIBLE_TIMESTAMP - 1, ..config };
+        assert_eq!(too_old.validate(), Err("Reference era init timestamp is too old"));
+    }
+}